@@ -1,10 +1,55 @@
+/*!
+NeuroEvolution of Augmenting Topologies.
+
+The crate can be used as a Rust library, or as a python module when built with the `python` feature.
+
+```
+use rust_neat::{Termination, NEAT};
+
+let mut neat = NEAT::new(150, 2, 1, Some(42)).unwrap();
+let termination = Termination {
+    max_generations: Some(10),
+    fitness_threshold: Some(3.9),
+    ..Termination::default()
+};
+let (best, reason) = neat
+    .run_with(
+        |genome| {
+            let mut error = 0.0;
+            for (a, b, expected) in [(0.0, 0.0, 0.0), (0.0, 1.0, 1.0), (1.0, 0.0, 1.0), (1.0, 1.0, 0.0)] {
+                error += (genome.compute(vec![a, b])?[0] - expected).powi(2);
+            }
+            Ok(4.0 - error)
+        },
+        &termination,
+    )
+    .unwrap();
+println!("best fitness: {} ({})", best.fitness, reason);
+```
+*/
+
+#![warn(missing_docs)]
+
 mod neat;
+pub use neat::*;
+
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
+#[cfg(feature = "python")]
 #[pymodule]
 fn rust_neat(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<neat::NEAT>()?;
     m.add_class::<neat::Genome>()?;
+    m.add_class::<neat::GenomeInfo>()?;
+    m.add_class::<neat::SpeciesInfo>()?;
+    m.add_class::<neat::StdOutReporter>()?;
+    m.add_class::<neat::Statistics>()?;
+    m.add_class::<neat::EvolvableSubstrate>()?;
+    m.add_class::<neat::Substrate>()?;
+    m.add_class::<neat::SubstrateNetwork>()?;
+    neat::binary::add_functions(m)?;
+    neat::env::add_functions(m)?;
 
     Ok(())
 }
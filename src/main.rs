@@ -1,38 +1,362 @@
-use crate::neat::Genome;
-use crate::neat::Gene;
-
-mod neat;
-
-// TODO remove main
-/// Main for testing purposes only
-fn main(){
-    for _ in 0..10 {
-        let mut g1 = Genome::new(0, 5, 5);
-
-        for i in 117..200 {
-            g1.genes.push(Gene {
-                enabled: true,
-                from: 0,
-                to: i,
-                hm: i,
-                weight: 0.1,
-            });
-
-            g1.genes.push(Gene {
-                enabled: true,
-                from: i,
-                to: 7,
-                hm: i * 2,
-                weight: 0.1,
-            });
+/*!
+Command-line experiment runner: evolves networks on the built-in tasks, resumes runs from their
+checkpoints and inspects or renders the saved genomes.
+
+The experiments are described by a TOML file, every key being optional:
+
+```toml
+task = "xor"
+population = 150
+generations = 300
+fitness_threshold = 3.9
+seed = 42
+checkpoint_interval = 10
+
+[settings]
+add_node_chance = 0.05
+```
+*/
+
+use clap::{Parser, Subcommand};
+use rust_neat::{
+    binary, set_settings, tasks, Genome, Settings, StdOutReporter, StopReason, Termination, NEAT,
+};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(name = "rust-neat", version, about = "Runs NEAT experiments")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs an experiment, saving its checkpoint, statistics and best genome in the output directory
+    Run {
+        /// Experiment file, the options below override its values
+        #[arg(long)]
+        config: Option<PathBuf>,
+        #[arg(long)]
+        task: Option<String>,
+        #[arg(long)]
+        generations: Option<u32>,
+        #[arg(long)]
+        population: Option<usize>,
+        #[arg(long)]
+        seed: Option<u64>,
+        #[arg(long, default_value = "out")]
+        out: PathBuf,
+        /// Doesn't print the progress of the run
+        #[arg(long)]
+        quiet: bool,
+    },
+    /// Resumes a run from its checkpoint, until the number of generations of the experiment
+    Resume {
+        checkpoint: PathBuf,
+        /// Total number of generations, replaces the one of the experiment
+        #[arg(long)]
+        generations: Option<u32>,
+        #[arg(long)]
+        quiet: bool,
+    },
+    /// Prints a summary of a genome saved as JSON or in the binary format
+    Inspect { genome: PathBuf },
+    /// Renders a genome as Graphviz DOT, or as SVG
+    Render {
+        genome: PathBuf,
+        #[arg(long)]
+        svg: bool,
+        /// Draws the disabled genes, DOT only
+        #[arg(long)]
+        show_disabled: bool,
+        /// Output file, the image is printed if not given
+        #[arg(long, short)]
+        out: Option<PathBuf>,
+    },
+}
+
+/// Description of an experiment, saved along with the run so that it can be resumed
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Experiment {
+    task: String,
+    population: usize,
+    generations: u32,
+    /// The run stops once a genome reaches it, defaults to the threshold of the task
+    fitness_threshold: Option<f64>,
+    /// A random seed is picked and saved if none is given
+    seed: Option<u64>,
+    /// Number of generations between two checkpoints
+    checkpoint_interval: u32,
+    /// Settings replacing the default ones
+    settings: toml::Table,
+}
+
+impl Default for Experiment {
+    fn default() -> Self {
+        Experiment {
+            task: String::from("xor"),
+            population: 150,
+            generations: 300,
+            fitness_threshold: None,
+            seed: None,
+            checkpoint_interval: 10,
+            settings: toml::Table::new(),
+        }
+    }
+}
+
+impl Experiment {
+    fn load(path: &Path) -> Result<Self> {
+        let experiment = toml::from_str(&fs::read_to_string(path)?)
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+        Ok(experiment)
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        Ok(fs::write(path, toml::to_string(self)?)?)
+    }
+
+    /// Returns the default settings, with the values of the experiment
+    fn get_settings(&self) -> Result<Settings> {
+        let mut settings = toml::Table::try_from(Settings::default())?;
+        for (key, value) in &self.settings {
+            if !settings.contains_key(key) {
+                return Err(format!("unknown setting '{}'", key).into());
+            }
+            settings.insert(key.clone(), value.clone());
         }
+        Ok(settings
+            .try_into()
+            .map_err(|err| format!("invalid settings: {}", err))?)
+    }
+}
+
+/// Reads a genome saved as JSON, or in the binary format
+fn load_genome(path: &Path) -> Result<Genome> {
+    let data = fs::read(path)?;
+    if data.starts_with(binary::MAGIC) {
+        Ok(Genome::from_bytes(&data)?)
+    } else {
+        Ok(Genome::from_json(std::str::from_utf8(&data)?)?)
+    }
+}
+
+/**
+Runs generations until the total number of generations of the experiment or the fitness threshold is
+reached, saving a checkpoint every `checkpoint_interval` generations.
+The statistics and the best genome are saved at the end.
+*/
+fn evolve(neat: &mut NEAT, experiment: &Experiment, out: &Path) -> Result<()> {
+    if experiment.checkpoint_interval == 0 {
+        return Err("the checkpoint interval must be at least 1".into());
+    }
+    let task = tasks::from_name(&experiment.task)?;
+    let fitness_threshold = experiment
+        .fitness_threshold
+        .unwrap_or(task.get_fitness_threshold());
+
+    let mut remaining = experiment
+        .generations
+        .saturating_sub(neat.get_generation() as u32);
+    let mut reason = StopReason::MaxGenerations;
+    while remaining > 0 {
+        let start = neat.get_generation();
+        let termination = Termination {
+            max_generations: Some(remaining.min(experiment.checkpoint_interval)),
+            fitness_threshold: Some(fitness_threshold),
+            ..Termination::default()
+        };
+        reason = neat
+            .run_with(|genome| task.evaluate(genome), &termination)?
+            .1;
+        remaining -= (neat.get_generation() - start) as u32;
+        fs::write(out.join("checkpoint.json"), neat.to_checkpoint().to_json()?)?;
+        if reason != StopReason::MaxGenerations {
+            break;
+        }
+    }
+
+    fs::write(out.join("statistics.csv"), neat.get_statistics().to_csv())?;
+    match neat.get_best_genome() {
+        Some(best) => {
+            fs::write(out.join("best.json"), best.to_json()?)?;
+            println!(
+                "Stopped ({}) after {} generations, best fitness: {} (genome {}, generation {})",
+                reason,
+                neat.get_generation(),
+                best.fitness,
+                best.id,
+                neat.get_best_generation().unwrap()
+            );
+        }
+        None => println!("No generation was run"),
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run(
+    config: Option<PathBuf>,
+    task: Option<String>,
+    generations: Option<u32>,
+    population: Option<usize>,
+    seed: Option<u64>,
+    out: PathBuf,
+    quiet: bool,
+) -> Result<()> {
+    let mut experiment = match config {
+        Some(path) => Experiment::load(&path)?,
+        None => Experiment::default(),
+    };
+    experiment.task = task.unwrap_or(experiment.task);
+    experiment.generations = generations.unwrap_or(experiment.generations);
+    experiment.population = population.unwrap_or(experiment.population);
+    // the seed is saved so that the run can be reproduced
+    let seed = *experiment
+        .seed
+        .insert(seed.or(experiment.seed).unwrap_or_else(rand::random));
+
+    set_settings(experiment.get_settings()?);
+    let task = tasks::from_name(&experiment.task)?;
+    fs::create_dir_all(&out)?;
+    experiment.save(&out.join("experiment.toml"))?;
+
+    let mut neat = NEAT::new(
+        experiment.population,
+        task.get_n_inputs(),
+        task.get_n_outputs(),
+        Some(seed),
+    )?;
+    if !quiet {
+        neat.add_reporter(Box::new(StdOutReporter::new()));
+    }
+    evolve(&mut neat, &experiment, &out)
+}
+
+/// The experiment is read from the directory of the checkpoint, where the run saved it
+fn resume(checkpoint: PathBuf, generations: Option<u32>, quiet: bool) -> Result<()> {
+    let out = checkpoint.parent().unwrap_or(Path::new("."));
+    let mut experiment = Experiment::load(&out.join("experiment.toml"))?;
+    if let Some(generations) = generations {
+        experiment.generations = generations;
+        experiment.save(&out.join("experiment.toml"))?;
+    }
+
+    let json = fs::read_to_string(&checkpoint)?;
+    let mut neat = NEAT::from_checkpoint(rust_neat::Checkpoint::from_json(&json)?);
+    if !quiet {
+        neat.add_reporter(Box::new(StdOutReporter::new()));
+    }
+    evolve(&mut neat, &experiment, out)
+}
+
+fn inspect(path: PathBuf) -> Result<()> {
+    let genome = load_genome(&path)?;
+    let enabled = genome.genes.iter().filter(|gene| gene.enabled).count();
+
+    println!("Genome {}", genome.id);
+    println!("Fitness: {}", genome.fitness);
+    println!(
+        "Inputs: {}, outputs: {}, hidden nodes: {}",
+        genome.get_n_inputs(),
+        genome.get_n_outputs(),
+        genome.get_nb_hidden_nodes()
+    );
+    println!("Genes: {} ({} enabled)", genome.genes.len(), enabled);
+    println!("Depth: {}", genome.get_depth());
+    for gene in &genome.genes {
+        println!("{}", gene);
+    }
+    Ok(())
+}
+
+fn render(path: PathBuf, svg: bool, show_disabled: bool, out: Option<PathBuf>) -> Result<()> {
+    let genome = load_genome(&path)?;
+    let image = if svg {
+        genome.to_svg()
+    } else {
+        genome.to_dot(show_disabled)
+    };
+    match out {
+        Some(out) => fs::write(out, image)?,
+        None => print!("{}", image),
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Run {
+            config,
+            task,
+            generations,
+            population,
+            seed,
+            out,
+            quiet,
+        } => run(config, task, generations, population, seed, out, quiet),
+        Command::Resume {
+            checkpoint,
+            generations,
+            quiet,
+        } => resume(checkpoint, generations, quiet),
+        Command::Inspect { genome } => inspect(genome),
+        Command::Render {
+            genome,
+            svg,
+            show_disabled,
+            out,
+        } => render(genome, svg, show_disabled, out),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn experiment_settings() {
+        let experiment: Experiment =
+            toml::from_str("task = \"xor\"\n[settings]\nadd_node_chance = 0.5\n").unwrap();
+        assert_eq!(experiment.population, 150);
+        let settings = experiment.get_settings().unwrap();
+        assert_eq!(settings.add_node_chance, 0.5);
+        assert_eq!(
+            settings.add_link_chance,
+            Settings::default().add_link_chance
+        );
+
+        let experiment: Experiment = toml::from_str("[settings]\nadd_nodes = 0.5\n").unwrap();
+        assert!(experiment.get_settings().is_err());
+        assert!(toml::from_str::<Experiment>("populaton = 10\n").is_err());
+    }
 
-        let nodes = g1.get_linkable_nodes(None);
-        println!("Is some: {}", nodes.is_some());
-        let nodes = nodes.unwrap();
-        println!("Node 0: {}, Node 1: {}", nodes.0, nodes.1);
-        let layer1 = g1.network.as_ref().unwrap().nodes[&nodes.0].layer;
-        let layer2 = g1.network.as_ref().unwrap().nodes[&nodes.1].layer;
-        assert!(layer1 <= layer2);
+    #[test]
+    fn experiment_round_trip() {
+        let mut experiment = Experiment {
+            seed: Some(3),
+            ..Experiment::default()
+        };
+        experiment
+            .settings
+            .insert(String::from("crossover_chance"), toml::Value::Float(0.5));
+        let read: Experiment = toml::from_str(&toml::to_string(&experiment).unwrap()).unwrap();
+        assert_eq!(read.seed, Some(3));
+        assert_eq!(read.settings, experiment.settings);
     }
 }
@@ -1,29 +1,92 @@
-#![allow(dead_code)]
-
+#[allow(clippy::module_inception)]
 mod neat;
 pub use neat::NEAT;
 
+mod activation;
+pub use activation::Activation;
+pub use activation::Activations;
+
+pub mod binary;
+
+#[cfg(feature = "serde")]
+mod checkpoint;
+#[cfg(feature = "serde")]
+pub use checkpoint::Checkpoint;
+
+mod codegen;
+pub use codegen::Language;
+
+mod dot;
+
+#[cfg(feature = "python")]
+pub mod env;
+
+mod error;
+pub use error::NeatError;
+
+mod es_hyperneat;
+pub use es_hyperneat::EvolvableSubstrate;
+
 mod gene;
 pub use gene::Gene;
 
 mod genome;
 pub use genome::Genome;
 
+mod hyperneat;
+pub use hyperneat::Substrate;
+pub use hyperneat::SubstrateNetwork;
+
+mod info;
+pub use info::GenomeInfo;
+pub use info::SpeciesInfo;
+
+mod innovation;
+pub use innovation::InnovationHistory;
+
 mod link;
-pub use link::LinkFrom;
-pub use link::LinkTo;
+use link::LinkFrom;
+use link::LinkTo;
 
 mod network;
-pub use network::Network;
+use network::Network;
+
+pub mod onnx;
 
 mod node;
-pub use node::Node;
-pub use node::NodeType;
+use node::Node;
+use node::NodeType;
+
+mod novelty;
+pub use novelty::NoveltySearch;
+
+mod pareto;
+
+mod statistics;
+pub use statistics::GenerationStats;
+pub use statistics::Statistics;
+
+mod svg;
+
+pub mod tasks;
+pub use tasks::Task;
+
+mod termination;
+pub use termination::StopReason;
+pub use termination::Termination;
 
 mod utils;
-pub use utils::Settings;
+pub use utils::get_settings;
+pub use utils::set_settings;
 pub use utils::sigmoid;
-pub use utils::SETTINGS;
+pub use utils::Settings;
+
+mod reporter;
+pub use reporter::Reporter;
+pub use reporter::StdOutReporter;
+
+mod rtneat;
+pub use rtneat::RealTime;
 
 mod species;
 pub use species::Species;
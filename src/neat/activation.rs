@@ -0,0 +1,162 @@
+use super::{sigmoid, NeatError, Settings};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/**
+Activation function of a node.
+The networks evolved by NEAT only use the sigmoid unless activations are mutated, the other functions
+are mostly useful for CPPNs, which HyperNEAT evolves to draw regular patterns.
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Activation {
+    /// `1 / (1 + exp(-steepness * x))`, the steepness being set in the settings
+    #[default]
+    Sigmoid,
+    /// `tanh(x)`
+    Tanh,
+    /// `max(x, 0)`
+    Relu,
+    /// `x`
+    Identity,
+    /// `exp(-x²)`
+    Gaussian,
+    /// `sin(x)`
+    Sin,
+    /// `|x|`
+    Abs,
+}
+
+impl Activation {
+    /// Every activation, in the order of their binary codes
+    pub const ALL: [Activation; 7] = [
+        Activation::Sigmoid,
+        Activation::Tanh,
+        Activation::Relu,
+        Activation::Identity,
+        Activation::Gaussian,
+        Activation::Sin,
+        Activation::Abs,
+    ];
+
+    /// Returns the activation of `x`
+    pub fn apply(self, x: f64) -> f64 {
+        match self {
+            Activation::Sigmoid => sigmoid(x),
+            Activation::Tanh => x.tanh(),
+            Activation::Relu => {
+                if x > 0.0 {
+                    x
+                } else {
+                    0.0
+                }
+            }
+            Activation::Identity => x,
+            Activation::Gaussian => (-x * x).exp(),
+            Activation::Sin => x.sin(),
+            Activation::Abs => x.abs(),
+        }
+    }
+
+    /// Name of the activation, as used in Python and in the generated code
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Activation::Sigmoid => "sigmoid",
+            Activation::Tanh => "tanh",
+            Activation::Relu => "relu",
+            Activation::Identity => "identity",
+            Activation::Gaussian => "gaussian",
+            Activation::Sin => "sin",
+            Activation::Abs => "abs",
+        }
+    }
+
+    /// Code of the activation in the binary format
+    pub fn to_code(self) -> u8 {
+        Activation::ALL.iter().position(|a| *a == self).unwrap() as u8
+    }
+
+    /// Returns the activation of a code of the binary format, None if it is unknown
+    pub fn from_code(code: u8) -> Option<Self> {
+        Activation::ALL.get(code as usize).copied()
+    }
+}
+
+/**
+Activations given by a run to the nodes of its genomes, and chance of an offspring getting a random
+activation. Each run has its own, initialized from the settings.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Activations {
+    /// Activation of the nodes added by mutations
+    pub hidden: Activation,
+    /// Activation of the output nodes
+    pub output: Activation,
+    /// Chance of an offspring getting a random activation on one of its hidden or output nodes
+    pub mutation_chance: f64,
+}
+
+impl Activations {
+    /// Returns the activations given in the settings
+    pub fn from_settings(settings: &Settings) -> Self {
+        Activations {
+            hidden: settings.hidden_activation,
+            output: settings.output_activation,
+            mutation_chance: settings.activation_mut_chance,
+        }
+    }
+}
+
+impl fmt::Display for Activation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+impl FromStr for Activation {
+    type Err = NeatError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let name = name.to_lowercase();
+        Activation::ALL
+            .into_iter()
+            .find(|activation| activation.get_name() == name)
+            .ok_or_else(|| {
+                NeatError::InvalidConfig(format!(
+                    "unknown activation '{}', expected one of {}",
+                    name,
+                    Activation::ALL.map(Activation::get_name).join(", ")
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activations() {
+        assert_eq!(Activation::Sigmoid.apply(0.0), 0.5);
+        assert_eq!(Activation::Gaussian.apply(0.0), 1.0);
+        assert_eq!(Activation::Gaussian.apply(2.0), (-4.0f64).exp());
+        assert_eq!(Activation::Relu.apply(-1.0), 0.0);
+        assert_eq!(Activation::Abs.apply(-1.5), 1.5);
+        for activation in Activation::ALL {
+            assert_eq!(
+                Activation::from_code(activation.to_code()),
+                Some(activation)
+            );
+            assert_eq!(
+                activation.to_string().parse::<Activation>().unwrap(),
+                activation
+            );
+        }
+        assert_eq!(Activation::from_code(7), None);
+        assert_eq!("Tanh".parse::<Activation>().unwrap(), Activation::Tanh);
+        assert!("softmax".parse::<Activation>().is_err());
+    }
+}
@@ -0,0 +1,403 @@
+/*!
+Compact binary format for genomes.
+
+All the values are little endian:
+- header: magic `NEAT` (4 bytes), version (u16), reserved (u16, 0), number of genomes (u32)
+- each genome: id (u32), inputs (u32), outputs (u32), nodes (u32), fitness (f64), number of genes (u32),
+  followed by its genes: historical marking (u32), from (u32), to (u32), weight (f64), enabled (u8),
+  then the number of nodes not using the sigmoid (u32) followed by their id (u32) and activation (u8)
+  in the order of `Activation::ALL`; version 1 had no activations
+- checksum: CRC-32 of everything before it (u32)
+*/
+
+use super::{Activation, Gene, Genome, NeatError};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+/// First bytes of the binary data
+pub const MAGIC: &[u8; 4] = b"NEAT";
+/// Version of the format written by `to_bytes`
+pub const VERSION: u16 = 2;
+
+const HEADER_SIZE: usize = 12;
+const GENOME_HEADER_SIZE: usize = 28;
+const GENE_SIZE: usize = 21;
+const ACTIVATIONS_HEADER_SIZE: usize = 4;
+const ACTIVATION_SIZE: usize = 5;
+const CHECKSUM_SIZE: usize = 4;
+
+/// CRC-32 (IEEE) of the data
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Writes the genomes in the binary format
+pub fn write_genomes(genomes: &[Genome]) -> Vec<u8> {
+    let size = HEADER_SIZE
+        + genomes
+            .iter()
+            .map(|genome| {
+                GENOME_HEADER_SIZE
+                    + genome.genes.len() * GENE_SIZE
+                    + ACTIVATIONS_HEADER_SIZE
+                    + genome.get_activations().len() * ACTIVATION_SIZE
+            })
+            .sum::<usize>()
+        + CHECKSUM_SIZE;
+    let mut data = Vec::with_capacity(size);
+
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&VERSION.to_le_bytes());
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&(genomes.len() as u32).to_le_bytes());
+    for genome in genomes {
+        data.extend_from_slice(&genome.id.to_le_bytes());
+        data.extend_from_slice(&genome.get_n_inputs().to_le_bytes());
+        data.extend_from_slice(&genome.get_n_outputs().to_le_bytes());
+        data.extend_from_slice(&genome.n_nodes.to_le_bytes());
+        data.extend_from_slice(&genome.fitness.to_le_bytes());
+        data.extend_from_slice(&(genome.genes.len() as u32).to_le_bytes());
+        for gene in &genome.genes {
+            data.extend_from_slice(&gene.hm.to_le_bytes());
+            data.extend_from_slice(&gene.from.to_le_bytes());
+            data.extend_from_slice(&gene.to.to_le_bytes());
+            data.extend_from_slice(&gene.weight.to_le_bytes());
+            data.push(gene.enabled as u8);
+        }
+        data.extend_from_slice(&(genome.get_activations().len() as u32).to_le_bytes());
+        for (node, activation) in genome.get_activations() {
+            data.extend_from_slice(&node.to_le_bytes());
+            data.push(activation.to_code());
+        }
+    }
+    let checksum = crc32(&data);
+    data.extend_from_slice(&checksum.to_le_bytes());
+    data
+}
+
+/// Reads genomes written with `write_genomes`
+pub fn read_genomes(data: &[u8]) -> Result<Vec<Genome>, NeatError> {
+    let mut reader = Reader { data, pos: 0 };
+
+    if reader.read_bytes(MAGIC.len())? != MAGIC {
+        return Err(NeatError::InvalidFormat(String::from(
+            "not a rust-neat genome file",
+        )));
+    }
+    let version = reader.read_u16()?;
+    // version 0 was never written
+    if version == 0 || version > VERSION {
+        return Err(NeatError::UnsupportedVersion {
+            version,
+            supported: VERSION,
+        });
+    }
+    reader.read_u16()?;
+    let n_genomes = reader.read_u32()? as usize;
+    reader.check_remaining_items(n_genomes, GENOME_HEADER_SIZE, CHECKSUM_SIZE)?;
+
+    // the checksum is verified before parsing, so that corrupted data is reported as such
+    let content_size = data.len() - CHECKSUM_SIZE;
+    let checksum = u32::from_le_bytes(data[content_size..].try_into().unwrap());
+    let actual = crc32(&data[..content_size]);
+    if checksum != actual {
+        return Err(NeatError::ChecksumMismatch {
+            expected: checksum,
+            actual,
+        });
+    }
+    reader.data = &data[..content_size];
+
+    let mut genomes = Vec::with_capacity(n_genomes);
+    for _ in 0..n_genomes {
+        let id = reader.read_u32()?;
+        let n_inputs = reader.read_u32()?;
+        let n_outputs = reader.read_u32()?;
+        let n_nodes = reader.read_u32()?;
+        let fitness = reader.read_f64()?;
+        let n_genes = reader.read_u32()? as usize;
+        reader.check_remaining_items(n_genes, GENE_SIZE, 0)?;
+
+        let mut genes = Vec::with_capacity(n_genes);
+        for _ in 0..n_genes {
+            genes.push(Gene {
+                hm: reader.read_u32()?,
+                from: reader.read_u32()?,
+                to: reader.read_u32()?,
+                weight: reader.read_f64()?,
+                enabled: reader.read_bytes(1)?[0] != 0,
+            });
+        }
+        let mut genome = Genome::from_genes(id, n_inputs, n_outputs, genes);
+        genome.n_nodes = n_nodes;
+        genome.fitness = fitness;
+        genome.check_genes().map_err(NeatError::InvalidFormat)?;
+        if version >= 2 {
+            let n_activations = reader.read_u32()? as usize;
+            reader.check_remaining(n_activations * ACTIVATION_SIZE)?;
+            for _ in 0..n_activations {
+                let node = reader.read_u32()?;
+                let code = reader.read_bytes(1)?[0];
+                let activation = Activation::from_code(code).ok_or_else(|| {
+                    NeatError::InvalidFormat(format!(
+                        "node {} of genome {} has an unknown activation {}",
+                        node, id, code
+                    ))
+                })?;
+                genome
+                    .set_activation(node, activation)
+                    .map_err(|err| NeatError::InvalidFormat(err.to_string()))?;
+            }
+        }
+        genomes.push(genome);
+    }
+
+    if reader.pos != content_size {
+        return Err(NeatError::InvalidFormat(format!(
+            "{} unexpected bytes before the checksum",
+            content_size - reader.pos
+        )));
+    }
+    Ok(genomes)
+}
+
+/// Saves the genomes in a binary file
+pub fn save_genomes(path: &str, genomes: &[Genome]) -> Result<(), NeatError> {
+    Ok(std::fs::write(path, write_genomes(genomes))?)
+}
+
+/// Loads the genomes saved with `save_genomes`
+pub fn load_genomes(path: &str) -> Result<Vec<Genome>, NeatError> {
+    read_genomes(&std::fs::read(path)?)
+}
+
+/// Saves the genomes in a binary file
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "save_genomes")]
+fn py_save_genomes(path: &str, genomes: Vec<Genome>) -> Result<(), NeatError> {
+    save_genomes(path, &genomes)
+}
+
+/// Loads the genomes saved with `save_genomes`
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "load_genomes")]
+fn py_load_genomes(path: &str) -> Result<Vec<Genome>, NeatError> {
+    load_genomes(path)
+}
+
+/// Adds the python functions of this module to `m`
+#[cfg(feature = "python")]
+pub fn add_functions(m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(py_save_genomes, m)?)?;
+    m.add_function(wrap_pyfunction!(py_load_genomes, m)?)?;
+    Ok(())
+}
+
+/// Reads little endian values, failing if the data is too short
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn check_remaining(&self, size: usize) -> Result<(), NeatError> {
+        if self.data.len() - self.pos < size {
+            return Err(NeatError::Truncated {
+                expected: self.pos.saturating_add(size),
+                got: self.data.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that `count` items of `size` bytes remain, followed by `extra` bytes
+    fn check_remaining_items(
+        &self,
+        count: usize,
+        size: usize,
+        extra: usize,
+    ) -> Result<(), NeatError> {
+        // a count too large to be addressed can't be in the data either
+        let total = count
+            .checked_mul(size)
+            .and_then(|total| total.checked_add(extra))
+            .unwrap_or(usize::MAX);
+        self.check_remaining(total)
+    }
+
+    fn read_bytes(&mut self, size: usize) -> Result<&'a [u8], NeatError> {
+        self.check_remaining(size)?;
+        self.pos += size;
+        Ok(&self.data[self.pos - size..self.pos])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, NeatError> {
+        Ok(u16::from_le_bytes(self.read_bytes(2)?.try_into().unwrap()))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, NeatError> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    fn read_f64(&mut self) -> Result<f64, NeatError> {
+        Ok(f64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::InnovationHistory;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn get_genomes() -> Vec<Genome> {
+        let mut history = InnovationHistory::new(3, 2);
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        (0..3)
+            .map(|id| {
+                let mut genome = Genome::new(id, 3, 2);
+                genome.mutate_weights(&mut rng);
+                genome.mutate_add_node(&mut history, &mut rng);
+                genome.mutate_add_link(&mut history, &mut rng);
+                genome.fitness = id as f64;
+                genome
+                    .set_activation(4, Activation::ALL[id as usize])
+                    .unwrap();
+                genome
+            })
+            .collect()
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut genomes = get_genomes();
+        let data = write_genomes(&genomes);
+        let mut read = read_genomes(&data).unwrap();
+        assert_eq!(read.len(), 3);
+        for (genome, read) in genomes.iter_mut().zip(read.iter_mut()) {
+            assert_eq!(genome.id, read.id);
+            assert_eq!(genome.fitness, read.fitness);
+            assert_eq!(genome.n_nodes, read.n_nodes);
+            assert_eq!(genome.get_activations(), read.get_activations());
+            assert_eq!(
+                genome.compute(vec![0.5, -1.0, 2.0]).unwrap(),
+                read.compute(vec![0.5, -1.0, 2.0]).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_data() {
+        let data = write_genomes(&get_genomes());
+
+        for size in [0, 5, HEADER_SIZE + 3] {
+            assert!(matches!(
+                read_genomes(&data[..size]),
+                Err(NeatError::Truncated { .. })
+            ));
+        }
+        assert!(matches!(
+            read_genomes(&data[..data.len() - 1]),
+            Err(NeatError::ChecksumMismatch { .. })
+        ));
+
+        let mut corrupted = data.clone();
+        corrupted[HEADER_SIZE + 20] ^= 0xFF;
+        assert!(matches!(
+            read_genomes(&corrupted),
+            Err(NeatError::ChecksumMismatch { .. })
+        ));
+        // the first gene going to the bias would be invalid, the checksum is checked first
+        let mut corrupted = data.clone();
+        corrupted[HEADER_SIZE + GENOME_HEADER_SIZE + 8..][..4].copy_from_slice(&0u32.to_le_bytes());
+        assert!(matches!(
+            read_genomes(&corrupted),
+            Err(NeatError::ChecksumMismatch { .. })
+        ));
+
+        let mut never_written = data.clone();
+        never_written[4..6].copy_from_slice(&0u16.to_le_bytes());
+        assert!(matches!(
+            read_genomes(&never_written),
+            Err(NeatError::UnsupportedVersion { version: 0, .. })
+        ));
+
+        let mut future = data.clone();
+        future[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            read_genomes(&future),
+            Err(NeatError::UnsupportedVersion { version, .. }) if version == VERSION + 1
+        ));
+
+        // the size of the genomes overflows
+        let reader = Reader {
+            data: &data,
+            pos: HEADER_SIZE,
+        };
+        assert!(matches!(
+            reader.check_remaining_items(usize::MAX / 2, GENE_SIZE, 0),
+            Err(NeatError::Truncated {
+                expected: usize::MAX,
+                ..
+            })
+        ));
+        assert!(matches!(
+            reader.check_remaining_items(1, usize::MAX, CHECKSUM_SIZE),
+            Err(NeatError::Truncated {
+                expected: usize::MAX,
+                ..
+            })
+        ));
+        let mut many_genomes = data.clone();
+        many_genomes[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            read_genomes(&many_genomes),
+            Err(NeatError::Truncated { .. })
+        ));
+
+        let mut wrong_magic = data.clone();
+        wrong_magic[0] = b'X';
+        assert!(matches!(
+            read_genomes(&wrong_magic),
+            Err(NeatError::InvalidFormat(_))
+        ));
+
+        let mut unknown_activation = data.clone();
+        let activation_pos = data.len() - CHECKSUM_SIZE - 1;
+        unknown_activation[activation_pos] = Activation::ALL.len() as u8;
+        let checksum = crc32(&unknown_activation[..data.len() - CHECKSUM_SIZE]);
+        unknown_activation[data.len() - CHECKSUM_SIZE..].copy_from_slice(&checksum.to_le_bytes());
+        assert!(matches!(
+            read_genomes(&unknown_activation),
+            Err(NeatError::InvalidFormat(_))
+        ));
+
+        let mut trailing = data[..data.len() - CHECKSUM_SIZE].to_vec();
+        trailing.push(0);
+        let checksum = crc32(&trailing);
+        trailing.extend_from_slice(&checksum.to_le_bytes());
+        assert!(matches!(
+            read_genomes(&trailing),
+            Err(NeatError::InvalidFormat(_))
+        ));
+    }
+}
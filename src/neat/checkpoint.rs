@@ -0,0 +1,180 @@
+/*!
+Checkpoints of a run, saved as JSON.
+A checkpoint holds everything needed to resume a run: the population, the species, the id counters,
+the innovation history, the best genomes, the statistics, the state of the random number generator,
+the novelty archive, the objectives of the last generation, the real-time state and the settings, so that a resumed run
+goes on exactly as if it hadn't been interrupted.
+The reporters aren't saved and have to be added again after loading.
+*/
+
+use super::{
+    Genome, InnovationHistory, NeatError, NoveltySearch, RealTime, Settings, Species, Statistics,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+pub const VERSION: u16 = 5;
+
+/// State of a run, see the module documentation
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Version of the checkpoint format, checkpoints of newer versions can't be loaded
+    pub version: u16,
+    /// Settings used by the run
+    pub settings: Settings,
+    /// Number of inputs of the networks
+    pub n_inputs: usize,
+    /// Number of outputs of the networks
+    pub n_outputs: usize,
+    /// Genomes of the current generation
+    pub population: Vec<Genome>,
+    /// Species of the current generation
+    pub species: Vec<Species>,
+    /// Id given to the next genome
+    pub genome_next_id: u32,
+    /// Id given to the next species
+    pub species_next_id: u32,
+    /// Number of generations run
+    pub generation: i32,
+    /// Innovation numbers given to the new links and nodes
+    pub innovations: InnovationHistory,
+    /// Max difference between two genomes of the same species
+    pub compatibility_threshold: f64,
+    /// Best genome found by the run
+    pub best_genome: Option<Genome>,
+    /// Generation in which the best genome was found
+    pub best_generation: i32,
+    /// Best genome of the last generation
+    pub generation_best: Option<Genome>,
+    /// Statistics of every generation
+    pub statistics: Statistics,
+    /// Missing in version 1, a random generator is created when loading these checkpoints
+    #[serde(default = "ChaCha8Rng::from_entropy")]
+    pub rng: ChaCha8Rng,
+    /// Missing before version 3, the runs of these checkpoints didn't use novelty search
+    #[serde(default)]
+    pub novelty: Option<NoveltySearch>,
+    /// Missing before version 4, the runs of these checkpoints had a single objective
+    #[serde(default)]
+    pub objectives: Vec<Vec<f64>>,
+    /// Missing before version 5, the runs of these checkpoints evolved by generations only
+    #[serde(default)]
+    pub real_time: Option<RealTime>,
+}
+
+impl Checkpoint {
+    /**
+    Returns the checkpoint as JSON.
+    Fails if it holds NaN or infinite values, which JSON can't represent and which couldn't be read back.
+    */
+    pub fn to_json(&self) -> Result<String, NeatError> {
+        self.check_finite().map_err(NeatError::Json)?;
+        serde_json::to_string(self).map_err(|err| NeatError::Json(err.to_string()))
+    }
+
+    /// Reads a checkpoint written by `to_json`, checking that it is consistent
+    pub fn from_json(json: &str) -> Result<Self, NeatError> {
+        let checkpoint: Checkpoint =
+            serde_json::from_str(json).map_err(|err| NeatError::Json(err.to_string()))?;
+        if checkpoint.version > VERSION {
+            return Err(NeatError::UnsupportedVersion {
+                version: checkpoint.version,
+                supported: VERSION,
+            });
+        }
+        checkpoint.check()?;
+        Ok(checkpoint)
+    }
+
+    /// Checks that every value saved as a number is finite
+    fn check_finite(&self) -> Result<(), String> {
+        for genome in self.get_genomes() {
+            genome.check_finite()?;
+        }
+        if !self.compatibility_threshold.is_finite() {
+            return Err(String::from(
+                "the compatibility threshold isn't a finite number",
+            ));
+        }
+        if let Some(stats) = self
+            .statistics
+            .get_generations()
+            .iter()
+            .find(|stats| !stats.is_finite())
+        {
+            return Err(format!(
+                "the statistics of generation {} aren't finite numbers",
+                stats.generation
+            ));
+        }
+        let vectors = self.objectives.iter().chain(
+            self.novelty
+                .iter()
+                .flat_map(|novelty| novelty.get_archive()),
+        );
+        for vector in vectors {
+            if vector.iter().any(|value| !value.is_finite()) {
+                return Err(String::from(
+                    "an objective or an archived behaviour isn't a finite number",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the genomes of the population, the representatives of the species and the best genomes
+    fn get_genomes(&self) -> impl Iterator<Item = &Genome> {
+        self.population
+            .iter()
+            .chain(self.species.iter().map(|species| &species.rep_genome))
+            .chain(&self.best_genome)
+            .chain(&self.generation_best)
+    }
+
+    /// Checks that the genomes and species are consistent with each other
+    fn check(&self) -> Result<(), NeatError> {
+        if self.population.is_empty() {
+            return Err(NeatError::InvalidFormat(String::from(
+                "the checkpoint has no population",
+            )));
+        }
+        for genome in self.get_genomes() {
+            if genome.get_n_inputs() as usize != self.n_inputs
+                || genome.get_n_outputs() as usize != self.n_outputs
+            {
+                return Err(NeatError::InvalidFormat(format!(
+                    "genome {} has {} inputs and {} outputs, expected {} and {}",
+                    genome.id,
+                    genome.get_n_inputs(),
+                    genome.get_n_outputs(),
+                    self.n_inputs,
+                    self.n_outputs
+                )));
+            }
+            genome.check_genes().map_err(NeatError::InvalidFormat)?;
+        }
+        if !self.objectives.is_empty() && self.objectives.len() != self.population.len() {
+            return Err(NeatError::InvalidFormat(format!(
+                "the checkpoint has the objectives of {} genomes but the population has {} genomes",
+                self.objectives.len(),
+                self.population.len()
+            )));
+        }
+        for species in &self.species {
+            if let Some(individual) = species
+                .population
+                .iter()
+                .find(|individual| **individual as usize >= self.population.len())
+            {
+                return Err(NeatError::InvalidFormat(format!(
+                    "species {} contains individual {} but the population has {} genomes",
+                    species.get_id(),
+                    individual,
+                    self.population.len()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,454 @@
+/*!
+Generation of standalone source code evaluating a genome.
+The weights are inlined and the nodes are computed in topological order, with the same operations as
+`Network::compute`, so the generated functions return exactly the same outputs.
+*/
+
+use super::get_settings;
+use super::{Activation, Genome, NeatError, Network, NodeType};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Language of the generated code
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    /// `pub fn evaluate(inputs: &[f64; N]) -> [f64; M]`
+    Rust,
+    /// `void evaluate(const double inputs[N], double outputs[M])`, using `math.h`
+    C,
+}
+
+impl Language {
+    /// Name of the function applying the activation, none for the identity
+    fn get_function(self, activation: Activation) -> Option<&'static str> {
+        match (self, activation) {
+            (_, Activation::Identity) => None,
+            (Language::C, Activation::Abs) => Some("fabs"),
+            _ => Some(activation.get_name()),
+        }
+    }
+
+    /// Definition of the function applying the activation, none when it is given by the language
+    fn get_definition(self, activation: Activation, steepness: f64) -> Option<String> {
+        let body = match (self, activation) {
+            (_, Activation::Identity) => return None,
+            (Language::Rust, Activation::Sigmoid) => {
+                format!("1.0 / (1.0 + (-({:?}) * x).exp())", steepness)
+            }
+            (Language::Rust, Activation::Tanh) => String::from("x.tanh()"),
+            (Language::Rust, Activation::Relu) => {
+                String::from("if x > 0.0 {\n        x\n    } else {\n        0.0\n    }")
+            }
+            (Language::Rust, Activation::Gaussian) => String::from("(-x * x).exp()"),
+            (Language::Rust, Activation::Sin) => String::from("x.sin()"),
+            (Language::Rust, Activation::Abs) => String::from("x.abs()"),
+            (Language::C, Activation::Sigmoid) => {
+                format!("return 1.0 / (1.0 + exp(-({:?}) * x));", steepness)
+            }
+            (Language::C, Activation::Relu) => String::from("return x > 0.0 ? x : 0.0;"),
+            (Language::C, Activation::Gaussian) => String::from("return exp(-x * x);"),
+            (Language::C, _) => return None,
+        };
+        Some(match self {
+            Language::Rust => format!(
+                "fn {}(x: f64) -> f64 {{\n    {}\n}}\n",
+                activation.get_name(),
+                body
+            ),
+            Language::C => format!(
+                "static double {}(double x) {{\n    {}\n}}\n",
+                activation.get_name(),
+                body
+            ),
+        })
+    }
+
+    /// Applies the activation to the expression
+    fn apply(self, activation: Activation, x: &str) -> String {
+        match self.get_function(activation) {
+            Some(function) => format!("{}({})", function, x),
+            None => String::from(x),
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = NeatError;
+
+    fn from_str(language: &str) -> Result<Self, Self::Err> {
+        match language.to_lowercase().as_str() {
+            "rust" => Ok(Language::Rust),
+            "c" => Ok(Language::C),
+            _ => Err(NeatError::InvalidConfig(format!(
+                "unknown language '{}', expected 'rust' or 'c'",
+                language
+            ))),
+        }
+    }
+}
+
+/// Code generation
+impl Genome {
+    /**
+    Returns the source code of a function evaluating the network, without any dependency.
+    NaN and infinite weights have no literal in the generated languages, they are an error.
+    */
+    pub fn codegen(&self, language: Language) -> Result<String, NeatError> {
+        let network = Network::new(self, self.get_n_inputs(), self.get_n_outputs());
+        for (id, node) in &network.nodes {
+            if let Some(pred) = node.pred.iter().find(|pred| !pred.weight.is_finite()) {
+                return Err(NeatError::NonFiniteWeight {
+                    genome_id: self.id,
+                    from: pred.from,
+                    to: *id,
+                });
+            }
+        }
+        let steepness = get_settings().sigmoid_steepness;
+        if !steepness.is_finite() {
+            return Err(NeatError::InvalidConfig(format!(
+                "sigmoid_steepness must be finite, got {}",
+                steepness
+            )));
+        }
+        let depths = network.get_depths();
+        let mut order: Vec<(usize, u32)> = depths.iter().map(|(id, depth)| (*depth, *id)).collect();
+        order.sort_unstable();
+
+        // value of a node when used by another one
+        let value = |id: u32| match network.nodes[&id].node_type {
+            NodeType::BIAS => String::from("1.0"),
+            NodeType::INPUT => format!("inputs[{}]", id - 1),
+            _ if network.nodes[&id].pred.is_empty() => String::from("0.0"),
+            _ => format!("n{}", id),
+        };
+
+        let mut nodes = Vec::new();
+        for (_, id) in order {
+            let node = &network.nodes[&id];
+            if node.pred.is_empty() {
+                continue;
+            }
+            // same order of operations as `Network::compute`
+            let mut sum = String::from("0.0");
+            for pred in &node.pred {
+                write!(sum, " + {} * {:?}", value(pred.from), pred.weight).unwrap();
+            }
+            nodes.push((id, language.apply(node.activation, &sum)));
+        }
+        let activations: BTreeSet<Activation> = nodes
+            .iter()
+            .map(|(id, _)| network.nodes[id].activation)
+            .collect();
+        let definitions: Vec<String> = activations
+            .into_iter()
+            .filter_map(|activation| language.get_definition(activation, steepness))
+            .collect();
+        let outputs: Vec<String> = (self.get_n_inputs() + 1
+            ..=self.get_n_inputs() + self.get_n_outputs())
+            .map(value)
+            .collect();
+
+        let mut code = String::new();
+        match language {
+            Language::Rust => {
+                let uses_inputs = nodes.iter().any(|(_, sum)| sum.contains("inputs["))
+                    || outputs.iter().any(|output| output.starts_with("inputs["));
+                writeln!(code, "// Generated by rust-neat from genome {}\n", self.id).unwrap();
+                for definition in &definitions {
+                    writeln!(code, "{}", definition).unwrap();
+                }
+                writeln!(
+                    code,
+                    "pub fn evaluate({}inputs: &[f64; {}]) -> [f64; {}] {{",
+                    if uses_inputs { "" } else { "_" },
+                    self.get_n_inputs(),
+                    self.get_n_outputs()
+                )
+                .unwrap();
+                for (id, value) in &nodes {
+                    writeln!(code, "    let n{} = {};", id, value).unwrap();
+                }
+                writeln!(code, "    [{}]\n}}", outputs.join(", ")).unwrap();
+            }
+            Language::C => {
+                writeln!(
+                    code,
+                    "/* Generated by rust-neat from genome {} */\n\n#include <math.h>\n",
+                    self.id
+                )
+                .unwrap();
+                for definition in &definitions {
+                    writeln!(code, "{}", definition).unwrap();
+                }
+                writeln!(
+                    code,
+                    "void evaluate(const double inputs[{}], double outputs[{}]) {{",
+                    self.get_n_inputs(),
+                    self.get_n_outputs()
+                )
+                .unwrap();
+                for (id, value) in &nodes {
+                    writeln!(code, "    const double n{} = {};", id, value).unwrap();
+                }
+                for (i, output) in outputs.iter().enumerate() {
+                    writeln!(code, "    outputs[{}] = {};", i, output).unwrap();
+                }
+                code.push_str("}\n");
+            }
+        }
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::InnovationHistory;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+    use std::process::Command;
+
+    fn get_genomes() -> Vec<Genome> {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut history = InnovationHistory::new(3, 2);
+        (0..8)
+            .map(|id| {
+                let mut genome = Genome::new(id, 3, 2);
+                genome.mutate_weights(&mut rng);
+                for _ in 0..id * 2 {
+                    genome.mutate_add_node(&mut history, &mut rng);
+                    genome.mutate_add_link(&mut history, &mut rng);
+                    genome.mutate_weights(&mut rng);
+                }
+                // half of the genomes use every activation
+                if id % 2 == 1 {
+                    for node in 4..genome.n_nodes {
+                        let activation =
+                            Activation::ALL[(node + id) as usize % Activation::ALL.len()];
+                        genome.set_activation(node, activation).unwrap();
+                    }
+                }
+                genome
+            })
+            .collect()
+    }
+
+    fn get_inputs() -> Vec<[f64; 3]> {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        (0..10)
+            .map(|_| [(); 3].map(|_| rng.gen_range(-2.0..2.0)))
+            .collect()
+    }
+
+    /// Checks the bits of the outputs printed by a generated program against `Genome::compute`
+    fn check_outputs(genomes: &mut [Genome], inputs: &[[f64; 3]], stdout: Vec<u8>) {
+        let mut generated = String::from_utf8(stdout)
+            .unwrap()
+            .lines()
+            .map(|line| f64::from_bits(line.parse().unwrap()))
+            .collect::<Vec<f64>>()
+            .into_iter();
+        for genome in genomes {
+            for inputs in inputs {
+                for expected in genome.compute(inputs.to_vec()).unwrap() {
+                    assert_eq!(generated.next().unwrap().to_bits(), expected.to_bits());
+                }
+            }
+        }
+        assert!(generated.next().is_none());
+    }
+
+    fn get_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("rust_neat_codegen_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn generated_rust_matches_compute() {
+        let mut genomes = get_genomes();
+        let inputs = get_inputs();
+
+        // one module per genome, the program prints the bits of every output
+        let mut program = String::new();
+        for genome in &genomes {
+            writeln!(
+                program,
+                "mod genome_{} {{\n{}}}",
+                genome.id,
+                genome.codegen(Language::Rust).unwrap()
+            )
+            .unwrap();
+        }
+        writeln!(program, "fn main() {{\n    let inputs = {:?};", inputs).unwrap();
+        for genome in &genomes {
+            writeln!(
+                program,
+                "    for inputs in &inputs {{\n        for output in genome_{}::evaluate(inputs) {{\n            \
+                 println!(\"{{}}\", output.to_bits());\n        }}\n    }}",
+                genome.id
+            )
+            .unwrap();
+        }
+        program.push_str("}\n");
+
+        let dir = get_dir("rust");
+        let source = dir.join("main.rs");
+        let binary = dir.join("main");
+        std::fs::write(&source, program).unwrap();
+        let status = Command::new(std::env::var("RUSTC").unwrap_or_else(|_| String::from("rustc")))
+            .arg("-O")
+            .arg("-o")
+            .arg(&binary)
+            .arg(&source)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let output = Command::new(&binary).output().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        check_outputs(&mut genomes, &inputs, output.stdout);
+    }
+
+    #[test]
+    fn generated_c_matches_compute() {
+        let cc = std::env::var("CC").unwrap_or_else(|_| String::from("cc"));
+        if Command::new(&cc).arg("--version").output().is_err() {
+            eprintln!(
+                "skipping the C code generation test, '{}' isn't available",
+                cc
+            );
+            return;
+        }
+        let mut genomes = get_genomes();
+        let inputs = get_inputs();
+
+        // one file per genome, as the activations are defined in each of them
+        let dir = get_dir("c");
+        let mut sources = Vec::new();
+        let mut program = String::from("#include <stdio.h>\n#include <string.h>\n\n");
+        for genome in &genomes {
+            let source = dir.join(format!("genome_{}.c", genome.id));
+            std::fs::write(
+                &source,
+                format!(
+                    "#define evaluate evaluate_{}\n{}",
+                    genome.id,
+                    genome.codegen(Language::C).unwrap()
+                ),
+            )
+            .unwrap();
+            sources.push(source);
+            writeln!(
+                program,
+                "void evaluate_{}(const double inputs[3], double outputs[2]);",
+                genome.id
+            )
+            .unwrap();
+        }
+        let inputs_c: Vec<String> = inputs
+            .iter()
+            .map(|inputs| format!("{{{:?}, {:?}, {:?}}}", inputs[0], inputs[1], inputs[2]))
+            .collect();
+        writeln!(
+            program,
+            "\nstatic void print(const double outputs[2]) {{\n    \
+             for (int i = 0; i < 2; i++) {{\n        unsigned long long bits;\n        \
+             memcpy(&bits, &outputs[i], sizeof(bits));\n        printf(\"%llu\\n\", bits);\n    }}\n}}\n\n\
+             int main(void) {{\n    const double inputs[{}][3] = {{{}}};\n    double outputs[2];",
+            inputs.len(),
+            inputs_c.join(", ")
+        )
+        .unwrap();
+        for genome in &genomes {
+            writeln!(
+                program,
+                "    for (int i = 0; i < {}; i++) {{\n        evaluate_{}(inputs[i], outputs);\n        \
+                 print(outputs);\n    }}",
+                inputs.len(),
+                genome.id
+            )
+            .unwrap();
+        }
+        program.push_str("    return 0;\n}\n");
+
+        let main = dir.join("main.c");
+        let binary = dir.join("main");
+        std::fs::write(&main, program).unwrap();
+        // without contraction into fused multiply-adds, the operations are the same as in Rust
+        let status = Command::new(&cc)
+            .args(["-std=c99", "-O2", "-ffp-contract=off", "-o"])
+            .arg(&binary)
+            .arg(&main)
+            .args(&sources)
+            .arg("-lm")
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let output = Command::new(&binary).output().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        check_outputs(&mut genomes, &inputs, output.stdout);
+    }
+
+    #[test]
+    fn generated_c() {
+        let mut genome = Genome::new(3, 2, 2);
+        genome.genes[0].weight = 0.5;
+        for gene in genome.genes.iter_mut().filter(|gene| gene.to == 4) {
+            gene.enabled = false;
+        }
+        let code = genome.codegen(Language::C).unwrap();
+        assert!(code.contains("/* Generated by rust-neat from genome 3 */"));
+        assert!(code.contains("void evaluate(const double inputs[2], double outputs[2]) {"));
+        assert!(
+            code.contains("const double n3 = sigmoid(0.0 + inputs[0] * 0.5 + inputs[1] * 0.0);")
+        );
+        assert!(code.contains("outputs[0] = n3;"));
+        assert!(code.contains("outputs[1] = 0.0;"));
+        assert!(code.contains("static double sigmoid(double x)"));
+        assert!(code.contains("return 1.0 / (1.0 + exp(-(4.9) * x));"));
+
+        genome.set_activation(3, Activation::Abs).unwrap();
+        let code = genome.codegen(Language::C).unwrap();
+        assert!(code.contains("const double n3 = fabs(0.0 + inputs[0] * 0.5 + inputs[1] * 0.0);"));
+        assert!(!code.contains("sigmoid"));
+        genome.set_activation(3, Activation::Gaussian).unwrap();
+        let code = genome.codegen(Language::C).unwrap();
+        assert!(code.contains("static double gaussian(double x) {\n    return exp(-x * x);\n}"));
+        assert!(code.contains("const double n3 = gaussian("));
+    }
+
+    #[test]
+    fn non_finite_weights() {
+        let mut genome = Genome::new(0, 2, 1);
+        genome.genes[1].weight = f64::INFINITY;
+        for language in [Language::Rust, Language::C] {
+            assert!(matches!(
+                genome.codegen(language),
+                Err(NeatError::NonFiniteWeight {
+                    genome_id: 0,
+                    from: 2,
+                    to: 3
+                })
+            ));
+        }
+        genome.genes[1].weight = f64::NAN;
+        assert!(genome.codegen(Language::Rust).is_err());
+
+        // a disabled gene isn't part of the network
+        genome.genes[1].enabled = false;
+        assert!(genome.codegen(Language::Rust).is_ok());
+    }
+
+    #[test]
+    fn parse_language() {
+        assert_eq!("rust".parse::<Language>().unwrap(), Language::Rust);
+        assert_eq!("C".parse::<Language>().unwrap(), Language::C);
+        assert!(matches!(
+            "python".parse::<Language>(),
+            Err(NeatError::InvalidConfig(_))
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+/*!
+Graphviz DOT export of genomes.
+The nodes are drawn from left to right by layer, the shape of a node depends on its type.
+Enabled connections are green for positive weights and red for negative ones,
+thicker the larger the weight is compared to the others.
+*/
+
+use super::{Genome, Network, NodeType};
+use std::fmt::Write;
+
+const POSITIVE_COLOR: &str = "#2e7d32";
+const NEGATIVE_COLOR: &str = "#c62828";
+pub(super) const DISABLED_COLOR: &str = "#9e9e9e";
+const MIN_PEN_WIDTH: f64 = 0.5;
+const MAX_PEN_WIDTH: f64 = 4.0;
+
+/// Fill color of the nodes of each type, shared with the SVG renderer
+pub(super) fn node_color(node_type: &NodeType) -> &'static str {
+    match node_type {
+        NodeType::BIAS => "#fff59d",
+        NodeType::INPUT => "#bbdefb",
+        NodeType::HIDDEN => "#ffffff",
+        NodeType::OUTPUT => "#c8e6c9",
+    }
+}
+
+/// Color of an enabled connection
+pub(super) fn edge_color(weight: f64) -> &'static str {
+    if weight < 0.0 {
+        NEGATIVE_COLOR
+    } else {
+        POSITIVE_COLOR
+    }
+}
+
+/// Width of an enabled connection, relative to the largest weight of the genome
+pub(super) fn edge_width(weight: f64, max_weight: f64) -> f64 {
+    if max_weight > 0.0 {
+        MIN_PEN_WIDTH + (MAX_PEN_WIDTH - MIN_PEN_WIDTH) * weight.abs() / max_weight
+    } else {
+        MIN_PEN_WIDTH
+    }
+}
+
+/// Largest absolute weight of the enabled genes
+pub(super) fn get_max_weight(genome: &Genome) -> f64 {
+    genome
+        .genes
+        .iter()
+        .filter(|gene| gene.enabled)
+        .map(|gene| gene.weight.abs())
+        .fold(0.0, f64::max)
+}
+
+fn node_attributes(node_type: &NodeType) -> String {
+    let shape = match node_type {
+        NodeType::BIAS => "diamond",
+        NodeType::INPUT => "box",
+        NodeType::HIDDEN => "circle",
+        NodeType::OUTPUT => "doublecircle",
+    };
+    if *node_type == NodeType::HIDDEN {
+        format!("shape={}", shape)
+    } else {
+        format!(
+            "shape={}, style=filled, fillcolor=\"{}\"",
+            shape,
+            node_color(node_type)
+        )
+    }
+}
+
+/// Visualization
+impl Genome {
+    /**
+    Returns the topology of the genome in the Graphviz DOT format.
+    Disabled genes are drawn as dashed grey lines when `show_disabled` is true.
+    */
+    pub fn to_dot(&self, show_disabled: bool) -> String {
+        let network = Network::new(self, self.get_n_inputs(), self.get_n_outputs());
+        let max_weight = get_max_weight(self);
+
+        let mut dot = String::from("digraph genome {\n    rankdir=LR;\n    node [fontsize=10];\n");
+
+        // one group per layer so that the nodes of a layer are aligned
+        let mut layers: Vec<i32> = network.nodes.values().map(|node| node.layer).collect();
+        layers.sort_unstable();
+        layers.dedup();
+        for layer in layers {
+            writeln!(dot, "    subgraph layer_{} {{\n        rank=same;", layer).unwrap();
+            for (id, node) in network.nodes.iter().filter(|(_, node)| node.layer == layer) {
+                writeln!(
+                    dot,
+                    "        {} [label=\"{}\", {}];",
+                    id,
+                    id,
+                    node_attributes(&node.node_type)
+                )
+                .unwrap();
+            }
+            dot.push_str("    }\n");
+        }
+        // nodes only linked by disabled genes aren't in the network
+        if show_disabled {
+            let mut missing: Vec<u32> = self
+                .genes
+                .iter()
+                .flat_map(|gene| [gene.from, gene.to])
+                .filter(|id| !network.nodes.contains_key(id))
+                .collect();
+            missing.sort_unstable();
+            missing.dedup();
+            for id in missing {
+                writeln!(
+                    dot,
+                    "    {} [label=\"{}\", {}, color=\"{}\"];",
+                    id,
+                    id,
+                    node_attributes(&NodeType::HIDDEN),
+                    DISABLED_COLOR
+                )
+                .unwrap();
+            }
+        }
+
+        for gene in &self.genes {
+            if gene.enabled {
+                writeln!(
+                    dot,
+                    "    {} -> {} [color=\"{}\", penwidth={:.2}, tooltip=\"{:.4}\"];",
+                    gene.from,
+                    gene.to,
+                    edge_color(gene.weight),
+                    edge_width(gene.weight, max_weight),
+                    gene.weight
+                )
+                .unwrap();
+            } else if show_disabled {
+                writeln!(
+                    dot,
+                    "    {} -> {} [color=\"{}\", style=dashed, tooltip=\"{:.4}\"];",
+                    gene.from, gene.to, DISABLED_COLOR, gene.weight
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::InnovationHistory;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn to_dot() {
+        let mut history = InnovationHistory::new(2, 1);
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut genome = Genome::new(0, 2, 1);
+        genome.genes[0].weight = -2.0;
+        genome.genes[1].weight = 1.0;
+        genome.mutate_add_node(&mut history, &mut rng);
+
+        let dot = genome.to_dot(false);
+        assert!(dot.starts_with("digraph genome {"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("0 [label=\"0\", shape=diamond"));
+        assert!(dot.contains("1 [label=\"1\", shape=box"));
+        assert!(dot.contains("3 [label=\"3\", shape=doublecircle"));
+        assert!(dot.contains("4 [label=\"4\", shape=circle]"));
+        assert_eq!(dot.matches("subgraph").count(), 3);
+        assert_eq!(dot.matches("->").count(), 3);
+        assert!(!dot.contains("dashed"));
+
+        let dot = genome.to_dot(true);
+        assert_eq!(dot.matches("->").count(), 4);
+        assert_eq!(dot.matches("style=dashed").count(), 1);
+        assert!(dot.contains(&format!("color=\"{}\", penwidth=4.00", NEGATIVE_COLOR)));
+    }
+}
@@ -0,0 +1,187 @@
+/*!
+Evaluation of genomes on Gymnasium environments, from python.
+
+The observations are flattened into the inputs of the network. Without `action_transform`, the outputs
+are mapped to the action space of the environment, found from the name of its class: the index of the
+largest output for `Discrete` spaces, and the outputs scaled from `[0, 1]` to `[low, high]` for bounded
+`Box` spaces. The outputs are clamped to `[0, 1]` first, so `Box` spaces need outputs using the sigmoid
+to use the whole range. The other spaces need an `action_transform`.
+*/
+
+use super::{Genome, NeatError};
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+
+/// Converts a number, a sequence or a numpy array to a flat list of numbers
+fn to_vec(value: &PyAny) -> PyResult<Vec<f64>> {
+    if let Ok(value) = value.extract::<f64>() {
+        return Ok(vec![value]);
+    }
+    if value.hasattr("ravel")? {
+        return value
+            .call_method0("ravel")?
+            .call_method0("tolist")?
+            .extract();
+    }
+    value.extract()
+}
+
+/// Index of the largest output, the first one on ties
+fn argmax(outputs: &[f64]) -> usize {
+    let mut best = 0;
+    for (i, output) in outputs.iter().enumerate() {
+        if *output > outputs[best] {
+            best = i;
+        }
+    }
+    best
+}
+
+/// Maps the outputs of the network to an action of the space
+fn get_action(py: Python, space: &PyAny, outputs: &[f64]) -> Result<PyObject, NeatError> {
+    // other spaces have `n` or `low` and `high` too, `MultiDiscrete` or `MultiBinary` for instance
+    let name: String = space.get_type().name()?.to_owned();
+    if name == "Discrete" {
+        let start: i64 = if space.hasattr("start")? {
+            space.getattr("start")?.extract()?
+        } else {
+            0
+        };
+        return Ok((start + argmax(outputs) as i64).into_py(py));
+    }
+    if name == "Box" {
+        let low = to_vec(space.getattr("low")?)?;
+        let high = to_vec(space.getattr("high")?)?;
+        if low.len() != outputs.len() {
+            return Err(NeatError::InvalidConfig(format!(
+                "the action space has {} dimensions but the network has {} outputs",
+                low.len(),
+                outputs.len()
+            )));
+        }
+        if low.iter().chain(&high).any(|bound| !bound.is_finite()) {
+            return Err(NeatError::InvalidConfig(String::from(
+                "the action space is unbounded, use action_transform",
+            )));
+        }
+        let action: Vec<f64> = outputs
+            .iter()
+            .zip(low.iter().zip(&high))
+            .map(|(output, (low, high))| low + output.clamp(0.0, 1.0) * (high - low))
+            .collect();
+        // the action is given with the shape and type of the space when numpy is available
+        return match py.import("numpy") {
+            Ok(numpy) => Ok(numpy
+                .call_method1("asarray", (action, space.getattr("dtype")?))?
+                .call_method1("reshape", (space.getattr("shape")?,))?
+                .into()),
+            Err(_) => Ok(action.into_py(py)),
+        };
+    }
+    Err(NeatError::InvalidConfig(format!(
+        "unsupported action space '{}', use action_transform",
+        name
+    )))
+}
+
+/// Runs one episode, returning its total reward
+fn run_episode(
+    py: Python,
+    genome: &mut Genome,
+    env: &PyAny,
+    max_steps: usize,
+    obs_transform: &Option<PyObject>,
+    action_transform: &Option<PyObject>,
+) -> Result<f64, NeatError> {
+    let get_inputs = |observation: &PyAny| -> PyResult<Vec<f64>> {
+        match obs_transform {
+            Some(transform) => to_vec(transform.call1(py, (observation,))?.as_ref(py)),
+            None => to_vec(observation),
+        }
+    };
+
+    // Gymnasium returns (observation, info), gym only the observation
+    let reset = env.call_method0("reset")?;
+    let mut observation = match reset.downcast::<PyTuple>() {
+        Ok(reset) if reset.len() == 2 => reset.get_item(0)?,
+        _ => reset,
+    };
+    let mut total_reward = 0.0;
+    for _ in 0..max_steps {
+        let outputs = genome.compute(get_inputs(observation)?)?;
+        let action = match action_transform {
+            Some(transform) => transform.call1(py, (outputs,))?,
+            None => get_action(py, env.getattr("action_space")?, &outputs)?,
+        };
+
+        // (observation, reward, terminated, truncated, info), or (observation, reward, done, info)
+        let step: &PyTuple = env
+            .call_method1("step", (action,))?
+            .downcast()
+            .map_err(PyErr::from)?;
+        observation = step.get_item(0)?;
+        total_reward += step.get_item(1)?.extract::<f64>()?;
+        let done = match step.len() {
+            5 => step.get_item(2)?.is_true()? || step.get_item(3)?.is_true()?,
+            _ => step.get_item(2)?.is_true()?,
+        };
+        if done {
+            break;
+        }
+    }
+    Ok(total_reward)
+}
+
+/**
+Runs `episodes` episodes of at most `max_steps` steps on an environment created by `env_factory`,
+and returns the mean total reward of the episodes.
+`obs_transform(observation)` and `action_transform(outputs)` replace the default conversions.
+*/
+#[pyfunction(obs_transform = "None", action_transform = "None")]
+pub fn evaluate_env(
+    py: Python,
+    mut genome: PyRefMut<Genome>,
+    env_factory: PyObject,
+    episodes: usize,
+    max_steps: usize,
+    obs_transform: Option<PyObject>,
+    action_transform: Option<PyObject>,
+) -> Result<f64, NeatError> {
+    if episodes == 0 {
+        return Err(NeatError::InvalidConfig(String::from(
+            "at least 1 episode is needed",
+        )));
+    }
+    let env = env_factory.call0(py)?;
+    let env = env.as_ref(py);
+
+    let mut total_reward = 0.0;
+    let mut result = Ok(());
+    for _ in 0..episodes {
+        match run_episode(
+            py,
+            &mut genome,
+            env,
+            max_steps,
+            &obs_transform,
+            &action_transform,
+        ) {
+            Ok(reward) => total_reward += reward,
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        }
+    }
+    // the environment is closed even if an episode failed
+    if env.hasattr("close")? {
+        env.call_method0("close")?;
+    }
+    result.map(|_| total_reward / episodes as f64)
+}
+
+/// Adds the python functions of this module to `m`
+pub fn add_functions(m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(evaluate_env, m)?)?;
+    Ok(())
+}
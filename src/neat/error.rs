@@ -0,0 +1,197 @@
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyIndexError, PyOSError, PyRuntimeError, PyTypeError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::PyErr;
+use std::fmt;
+
+/// Errors that can happen while building or running NEAT
+#[derive(Debug)]
+pub enum NeatError {
+    /// The number of inputs given to a network doesn't match its number of input nodes
+    WrongInputLength {
+        /// Number of inputs of the network
+        expected: usize,
+        /// Number of inputs given
+        got: usize,
+    },
+    /// The fitness function returned something that isn't a number
+    NonNumericFitness {
+        /// Id of the evaluated genome
+        genome_id: u32,
+        /// Python type of the returned value
+        type_name: String,
+    },
+    /// The fitness function returned NaN
+    NanFitness {
+        /// Id of the evaluated genome
+        genome_id: u32,
+    },
+    /// The fitness function returned an infinite value
+    InfiniteFitness {
+        /// Id of the evaluated genome
+        genome_id: u32,
+    },
+    /// The evaluation of a genome returned a vector whose length differs from the other genomes
+    WrongVectorLength {
+        /// Id of the evaluated genome
+        genome_id: u32,
+        /// Length of the vectors of the other genomes
+        expected: usize,
+        /// Length of the returned vector
+        got: usize,
+    },
+    /// There is no genome at this index in the population
+    IndexOutOfRange {
+        /// Index given
+        index: usize,
+        /// Size of the population
+        len: usize,
+    },
+    /// The fitness function raised an exception, or returned an error
+    Callback(Box<dyn std::error::Error + Send + Sync>),
+    /// The parameters given to NEAT can't be used
+    InvalidConfig(String),
+    /// Reading or writing a file failed
+    Io(std::io::Error),
+    /// A genome couldn't be read from JSON
+    Json(String),
+    /// The binary data ends before the genomes it contains
+    Truncated {
+        /// Number of bytes needed
+        expected: usize,
+        /// Number of bytes given
+        got: usize,
+    },
+    /// The binary data was written by a newer version of the format
+    UnsupportedVersion {
+        /// Version of the data
+        version: u16,
+        /// Latest version that can be read
+        supported: u16,
+    },
+    /// The checksum of the binary data doesn't match its content
+    ChecksumMismatch {
+        /// Checksum written in the data
+        expected: u32,
+        /// Checksum of the content
+        actual: u32,
+    },
+    /// The binary data isn't a valid genome file
+    InvalidFormat(String),
+    /// A weight of the genome is NaN or infinite, it can't be written as source code
+    NonFiniteWeight {
+        /// Id of the genome
+        genome_id: u32,
+        /// Node the link comes from
+        from: u32,
+        /// Node the link goes to
+        to: u32,
+    },
+}
+
+impl fmt::Display for NeatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::WrongInputLength { expected, got } => {
+                write!(f, "expected {} inputs, got {}", expected, got)
+            }
+            Self::NonNumericFitness {
+                genome_id,
+                type_name,
+            } => write!(
+                f,
+                "fitness of genome {} must be a number, got '{}'",
+                genome_id, type_name
+            ),
+            Self::NanFitness { genome_id } => write!(f, "fitness of genome {} is NaN", genome_id),
+            Self::InfiniteFitness { genome_id } => {
+                write!(f, "fitness of genome {} is infinite", genome_id)
+            }
+            Self::WrongVectorLength {
+                genome_id,
+                expected,
+                got,
+            } => write!(
+                f,
+                "evaluation of genome {} returned {} values, expected {}",
+                genome_id, got, expected
+            ),
+            Self::IndexOutOfRange { index, len } => write!(
+                f,
+                "genome index {} is out of range, the population has {} genomes",
+                index, len
+            ),
+            Self::Callback(err) => write!(f, "fitness function raised an exception: {}", err),
+            Self::InvalidConfig(msg) => write!(f, "invalid config: {}", msg),
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Json(msg) => write!(f, "invalid JSON: {}", msg),
+            Self::Truncated { expected, got } => write!(
+                f,
+                "truncated data: expected at least {} bytes, got {}",
+                expected, got
+            ),
+            Self::UnsupportedVersion { version, supported } => write!(
+                f,
+                "format version {} is not supported, the latest supported version is {}",
+                version, supported
+            ),
+            Self::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch: expected {:#010x}, computed {:#010x}",
+                expected, actual
+            ),
+            Self::InvalidFormat(msg) => write!(f, "invalid format: {}", msg),
+            Self::NonFiniteWeight {
+                genome_id,
+                from,
+                to,
+            } => write!(
+                f,
+                "weight of the link {} -> {} of genome {} is not finite",
+                from, to, genome_id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NeatError {}
+
+#[cfg(feature = "python")]
+impl From<PyErr> for NeatError {
+    fn from(err: PyErr) -> Self {
+        Self::Callback(Box::new(err))
+    }
+}
+
+impl From<std::io::Error> for NeatError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+#[cfg(feature = "python")]
+impl From<NeatError> for PyErr {
+    fn from(err: NeatError) -> Self {
+        match err {
+            // the original exception is raised again so that its traceback is kept
+            NeatError::Callback(err) => match err.downcast::<PyErr>() {
+                Ok(err) => *err,
+                Err(err) => PyRuntimeError::new_err(err.to_string()),
+            },
+            NeatError::NonNumericFitness { .. } => PyTypeError::new_err(err.to_string()),
+            NeatError::Io(err) => PyOSError::new_err(err.to_string()),
+            NeatError::IndexOutOfRange { .. } => PyIndexError::new_err(err.to_string()),
+            NeatError::WrongInputLength { .. }
+            | NeatError::NanFitness { .. }
+            | NeatError::InfiniteFitness { .. }
+            | NeatError::WrongVectorLength { .. }
+            | NeatError::InvalidConfig(_)
+            | NeatError::Json(_)
+            | NeatError::Truncated { .. }
+            | NeatError::UnsupportedVersion { .. }
+            | NeatError::ChecksumMismatch { .. }
+            | NeatError::InvalidFormat(_)
+            | NeatError::NonFiniteWeight { .. } => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
@@ -0,0 +1,540 @@
+/*!
+ES-HyperNEAT: HyperNEAT where the CPPN also decides the placement and number of the hidden nodes.
+
+Only the inputs and outputs have fixed coordinates, on a 2D substrate covering `[-1, 1]²`. From each
+input, the square is recursively divided in quadrants, more finely where the weights given by the CPPN
+vary more than `division_threshold`, between `initial_depth` and `max_depth` divisions. Quadrants whose
+variance is below `variance_threshold` are then extracted as points, which become hidden nodes only if
+they lie in a band: their weight must differ by more than `band_threshold` from both neighbours along
+one of the axes. The search is repeated from the new hidden nodes `iteration_level` times, and once
+from each output towards the hidden nodes. The hidden nodes that aren't on a path from an input to an
+output are finally removed.
+*/
+
+use super::{Activation, Genome, NeatError, Substrate, SubstrateNetwork};
+#[cfg(feature = "python")]
+use pyo3::*;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Quadrant of the quadtree, with the weight given by the CPPN at its center
+struct Quadrant {
+    x: f64,
+    y: f64,
+    /// Half of the side of the quadrant, the distance from its center to its neighbours
+    width: f64,
+    depth: u32,
+    weight: f64,
+    children: Vec<usize>,
+}
+
+/// Largest `max_depth`, a quadtree of this depth has about 4^10 (a million) quadrants
+const MAX_DEPTH: u32 = 10;
+/// Largest `iteration_level`
+const MAX_ITERATION_LEVEL: u32 = 10;
+
+/// Node of the network being discovered
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum SubstrateNode {
+    Input(usize),
+    Hidden(usize),
+    Output(usize),
+}
+
+/// Substrate whose hidden nodes are discovered from the CPPN
+#[cfg_attr(feature = "python", pyclass(module = "rust_neat"))]
+#[derive(Clone, Debug)]
+pub struct EvolvableSubstrate {
+    inputs: Vec<Vec<f64>>,
+    outputs: Vec<Vec<f64>>,
+    /// Number of divisions of every quadrant, 2 by default (a 4x4 grid)
+    pub initial_depth: u32,
+    /// Largest number of divisions, 3 by default (an 8x8 grid), at most 10
+    pub max_depth: u32,
+    /// Quadrants whose weights vary more than this are divided further, 0.03 by default
+    pub division_threshold: f64,
+    /// Quadrants whose weights vary less than this are extracted as a single point, 0.03 by default
+    pub variance_threshold: f64,
+    /// Points differing less than this from their neighbours aren't expressed, 0.3 by default
+    pub band_threshold: f64,
+    /// Number of searches from the hidden nodes, 1 by default, at most 10
+    pub iteration_level: u32,
+    /// Weight of the links whose CPPN output is 1 in absolute value, 3.0 by default
+    pub max_weight: f64,
+    /// Activation of the hidden and output nodes of the networks
+    pub activation: Activation,
+}
+
+impl EvolvableSubstrate {
+    /// Creates a substrate from the 2D coordinates of its inputs and outputs
+    pub fn new(inputs: Vec<Vec<f64>>, outputs: Vec<Vec<f64>>) -> Result<Self, NeatError> {
+        if inputs.is_empty() || outputs.is_empty() {
+            return Err(NeatError::InvalidConfig(format!(
+                "substrates need at least 1 input and 1 output, got {} inputs and {} outputs",
+                inputs.len(),
+                outputs.len()
+            )));
+        }
+        if let Some(node) = inputs.iter().chain(&outputs).find(|node| node.len() != 2) {
+            return Err(NeatError::InvalidConfig(format!(
+                "node {:?} has {} coordinates, expected 2",
+                node,
+                node.len()
+            )));
+        }
+        Ok(EvolvableSubstrate {
+            inputs,
+            outputs,
+            initial_depth: 2,
+            max_depth: 3,
+            division_threshold: 0.03,
+            variance_threshold: 0.03,
+            band_threshold: 0.3,
+            iteration_level: 1,
+            max_weight: 3.0,
+            activation: Activation::Sigmoid,
+        })
+    }
+
+    /// Coordinates of the input nodes
+    pub fn get_inputs(&self) -> &[Vec<f64>] {
+        &self.inputs
+    }
+
+    /// Coordinates of the output nodes
+    pub fn get_outputs(&self) -> &[Vec<f64>] {
+        &self.outputs
+    }
+
+    /// Checks that the CPPN takes 2 pairs of coordinates, and the parameters of the substrate
+    fn check(&self, cppn: &Genome) -> Result<(), NeatError> {
+        if cppn.get_n_inputs() != 4 || !(1..=2).contains(&cppn.get_n_outputs()) {
+            return Err(NeatError::InvalidConfig(format!(
+                "the CPPN has {} inputs and {} outputs, expected 4 inputs and 1 or 2 outputs",
+                cppn.get_n_inputs(),
+                cppn.get_n_outputs()
+            )));
+        }
+        self.check_parameters()
+    }
+
+    /// Checks the parameters of the substrate, the search being exponential in the depth
+    fn check_parameters(&self) -> Result<(), NeatError> {
+        if self.initial_depth == 0 || self.initial_depth > self.max_depth {
+            return Err(NeatError::InvalidConfig(format!(
+                "the depths must verify 1 <= initial_depth <= max_depth, got {} and {}",
+                self.initial_depth, self.max_depth
+            )));
+        }
+        if self.max_depth > MAX_DEPTH {
+            return Err(NeatError::InvalidConfig(format!(
+                "the max depth must be at most {}, got {}",
+                MAX_DEPTH, self.max_depth
+            )));
+        }
+        if self.iteration_level > MAX_ITERATION_LEVEL {
+            return Err(NeatError::InvalidConfig(format!(
+                "the iteration level must be at most {}, got {}",
+                MAX_ITERATION_LEVEL, self.iteration_level
+            )));
+        }
+        for (name, threshold) in [
+            ("division", self.division_threshold),
+            ("variance", self.variance_threshold),
+            ("band", self.band_threshold),
+        ] {
+            if threshold.is_nan() || threshold < 0.0 {
+                return Err(NeatError::InvalidConfig(format!(
+                    "the {} threshold must be positive, got {}",
+                    name, threshold
+                )));
+            }
+        }
+        if self.max_weight.is_nan() || self.max_weight <= 0.0 {
+            return Err(NeatError::InvalidConfig(format!(
+                "the max weight must be positive, got {}",
+                self.max_weight
+            )));
+        }
+        Ok(())
+    }
+
+    /// Output of the CPPN for the link between `node` and `point`, `outgoing` if it goes from `node`
+    fn query(
+        cppn: &mut Genome,
+        node: &[f64],
+        point: [f64; 2],
+        outgoing: bool,
+    ) -> Result<f64, NeatError> {
+        let (weight, _) = if outgoing {
+            Substrate::query(cppn, node, &point)?
+        } else {
+            Substrate::query(cppn, &point, node)?
+        };
+        Ok(weight)
+    }
+
+    /// Divides the substrate around `node`, returns the quadrants, the first one being the root
+    fn divide(
+        &self,
+        cppn: &mut Genome,
+        node: &[f64],
+        outgoing: bool,
+    ) -> Result<Vec<Quadrant>, NeatError> {
+        let mut quadrants = vec![Quadrant {
+            x: 0.0,
+            y: 0.0,
+            width: 1.0,
+            depth: 0,
+            weight: 0.0,
+            children: Vec::new(),
+        }];
+        let mut queue = VecDeque::from([0]);
+        while let Some(parent) = queue.pop_front() {
+            let (x, y, width, depth) = {
+                let quadrant = &quadrants[parent];
+                (
+                    quadrant.x,
+                    quadrant.y,
+                    quadrant.width / 2.0,
+                    quadrant.depth + 1,
+                )
+            };
+            for (dx, dy) in [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)] {
+                let (x, y) = (x + dx * width, y + dy * width);
+                quadrants.push(Quadrant {
+                    x,
+                    y,
+                    width,
+                    depth,
+                    weight: EvolvableSubstrate::query(cppn, node, [x, y], outgoing)?,
+                    children: Vec::new(),
+                });
+                let child = quadrants.len() - 1;
+                quadrants[parent].children.push(child);
+            }
+            if depth < self.initial_depth
+                || (depth < self.max_depth
+                    && get_variance(&quadrants, parent) > self.division_threshold)
+            {
+                queue.extend(&quadrants[parent].children);
+            }
+        }
+        Ok(quadrants)
+    }
+
+    /// Returns the points of the quadrant lying in a band, with their weight
+    fn extract(
+        &self,
+        cppn: &mut Genome,
+        node: &[f64],
+        outgoing: bool,
+        quadrants: &[Quadrant],
+        quadrant: usize,
+        points: &mut Vec<([f64; 2], f64)>,
+    ) -> Result<(), NeatError> {
+        for child in &quadrants[quadrant].children {
+            let Quadrant {
+                x,
+                y,
+                width,
+                weight,
+                ..
+            } = quadrants[*child];
+            if get_variance(quadrants, *child) >= self.variance_threshold {
+                self.extract(cppn, node, outgoing, quadrants, *child, points)?;
+                continue;
+            }
+            let mut difference = |dx: f64, dy: f64| -> Result<f64, NeatError> {
+                let neighbour = [x + dx * width, y + dy * width];
+                Ok((weight - EvolvableSubstrate::query(cppn, node, neighbour, outgoing)?).abs())
+            };
+            let horizontal = difference(-1.0, 0.0)?.min(difference(1.0, 0.0)?);
+            let vertical = difference(0.0, -1.0)?.min(difference(0.0, 1.0)?);
+            if horizontal.max(vertical) > self.band_threshold {
+                points.push(([x, y], weight));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the points found from the node, with their weight
+    fn search(
+        &self,
+        cppn: &mut Genome,
+        node: &[f64],
+        outgoing: bool,
+    ) -> Result<Vec<([f64; 2], f64)>, NeatError> {
+        let quadrants = self.divide(cppn, node, outgoing)?;
+        let mut points = Vec::new();
+        self.extract(cppn, node, outgoing, &quadrants, 0, &mut points)?;
+        Ok(points)
+    }
+
+    /// Builds the network discovered from the CPPN
+    pub fn build_network(&self, cppn: &mut Genome) -> Result<SubstrateNetwork, NeatError> {
+        self.check(cppn)?;
+        let mut hidden: Vec<[f64; 2]> = Vec::new();
+        let mut hidden_ids: HashMap<(u64, u64), usize> = HashMap::new();
+        let mut links: BTreeMap<(SubstrateNode, SubstrateNode), f64> = BTreeMap::new();
+        let weight = |output: f64| output.clamp(-1.0, 1.0) * self.max_weight;
+
+        // from the inputs, then from the new hidden nodes
+        let mut sources: Vec<(SubstrateNode, Vec<f64>)> = self
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(i, input)| (SubstrateNode::Input(i), input.clone()))
+            .collect();
+        for _ in 0..=self.iteration_level {
+            let mut new_sources = Vec::new();
+            for (source, coordinates) in sources {
+                for (point, output) in self.search(cppn, &coordinates, true)? {
+                    let key = (point[0].to_bits(), point[1].to_bits());
+                    let id = *hidden_ids.entry(key).or_insert_with(|| {
+                        hidden.push(point);
+                        new_sources.push((SubstrateNode::Hidden(hidden.len() - 1), point.to_vec()));
+                        hidden.len() - 1
+                    });
+                    links.insert((source, SubstrateNode::Hidden(id)), weight(output));
+                }
+            }
+            sources = new_sources;
+        }
+        // from the hidden nodes to the outputs
+        for (i, output) in self.outputs.iter().enumerate() {
+            for (point, value) in self.search(cppn, output, false)? {
+                if let Some(id) = hidden_ids.get(&(point[0].to_bits(), point[1].to_bits())) {
+                    links.insert(
+                        (SubstrateNode::Hidden(*id), SubstrateNode::Output(i)),
+                        weight(value),
+                    );
+                }
+            }
+        }
+
+        // only keeps the hidden nodes reachable from the inputs and reaching an output
+        let reachable = |forward: bool| {
+            let mut reached: Vec<SubstrateNode> = links
+                .keys()
+                .map(|(from, to)| if forward { *from } else { *to })
+                .filter(|node| !matches!(node, SubstrateNode::Hidden(_)))
+                .collect();
+            let mut visited = vec![false; hidden.len()];
+            while let Some(node) = reached.pop() {
+                for (from, to) in links.keys() {
+                    let (current, next) = if forward { (from, to) } else { (to, from) };
+                    if let (true, SubstrateNode::Hidden(id)) = (*current == node, next) {
+                        if !visited[*id] {
+                            visited[*id] = true;
+                            reached.push(*next);
+                        }
+                    }
+                }
+            }
+            visited
+        };
+        let (from_inputs, to_outputs) = (reachable(true), reachable(false));
+        let mut indices = vec![None; hidden.len()];
+        let mut coordinates: Vec<Vec<f64>> = self.inputs.clone();
+        for (id, point) in hidden.iter().enumerate() {
+            if from_inputs[id] && to_outputs[id] {
+                indices[id] = Some(coordinates.len());
+                coordinates.push(point.to_vec());
+            }
+        }
+        let n_hidden = coordinates.len() - self.inputs.len();
+        coordinates.extend(self.outputs.iter().cloned());
+
+        let index = |node: SubstrateNode| match node {
+            SubstrateNode::Input(i) => Some(i),
+            SubstrateNode::Hidden(id) => indices[id],
+            SubstrateNode::Output(i) => Some(self.inputs.len() + n_hidden + i),
+        };
+        let links = links
+            .iter()
+            .filter_map(|((from, to), weight)| Some((index(*from)?, index(*to)?, *weight)))
+            .collect();
+
+        let origin = [0.0, 0.0];
+        let bias = coordinates[self.inputs.len()..]
+            .iter()
+            .map(|node| Ok(Substrate::query(cppn, &origin, node)?.1.map_or(0.0, weight)))
+            .collect::<Result<Vec<f64>, NeatError>>()?;
+        SubstrateNetwork::new(
+            coordinates,
+            self.inputs.len(),
+            self.outputs.len(),
+            bias,
+            links,
+            self.activation,
+        )
+    }
+}
+
+/// Variance of the weights of the leaves of the quadrant, 0 for a leaf
+fn get_variance(quadrants: &[Quadrant], quadrant: usize) -> f64 {
+    let mut weights = Vec::new();
+    let mut stack = vec![quadrant];
+    while let Some(current) = stack.pop() {
+        match quadrants[current].children.as_slice() {
+            [] => weights.push(quadrants[current].weight),
+            children => stack.extend(children),
+        }
+    }
+    if weights.len() < 2 {
+        return 0.0;
+    }
+    let mean = weights.iter().sum::<f64>() / weights.len() as f64;
+    weights
+        .iter()
+        .map(|weight| (weight - mean).powi(2))
+        .sum::<f64>()
+        / weights.len() as f64
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl EvolvableSubstrate {
+    /// Creates a substrate from the 2D coordinates of its inputs and outputs
+    #[new]
+    #[args(
+        inputs,
+        outputs,
+        initial_depth = "2",
+        max_depth = "3",
+        division_threshold = "0.03",
+        variance_threshold = "0.03",
+        band_threshold = "0.3",
+        iteration_level = "1",
+        max_weight = "3.0",
+        activation = "\"sigmoid\""
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        inputs: Vec<Vec<f64>>,
+        outputs: Vec<Vec<f64>>,
+        initial_depth: u32,
+        max_depth: u32,
+        division_threshold: f64,
+        variance_threshold: f64,
+        band_threshold: f64,
+        iteration_level: u32,
+        max_weight: f64,
+        activation: &str,
+    ) -> Result<Self, NeatError> {
+        let mut substrate = EvolvableSubstrate::new(inputs, outputs)?;
+        substrate.initial_depth = initial_depth;
+        substrate.max_depth = max_depth;
+        substrate.division_threshold = division_threshold;
+        substrate.variance_threshold = variance_threshold;
+        substrate.band_threshold = band_threshold;
+        substrate.iteration_level = iteration_level;
+        substrate.max_weight = max_weight;
+        substrate.activation = activation.parse()?;
+        substrate.check_parameters()?;
+        Ok(substrate)
+    }
+
+    #[getter(inputs)]
+    fn py_inputs(&self) -> Vec<Vec<f64>> {
+        self.inputs.clone()
+    }
+
+    #[getter(outputs)]
+    fn py_outputs(&self) -> Vec<Vec<f64>> {
+        self.outputs.clone()
+    }
+
+    /// Builds the network discovered from the CPPN, which takes the coordinates of both nodes
+    #[pyo3(name = "build_network")]
+    fn py_build_network(&self, mut cppn: PyRefMut<Genome>) -> Result<SubstrateNetwork, NeatError> {
+        self.build_network(&mut cppn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::Gene;
+
+    /**
+    CPPN giving `gaussian(8 * (x2 - 0.125))`, plus `gaussian(8 * (x1 - 0.125))` if `source` is true:
+    a vertical band of points at x = 0.125
+    */
+    fn get_cppn(source: bool) -> Genome {
+        let mut genes = Vec::new();
+        let mut add_gene = |from: u32, to: u32, weight: f64| {
+            genes.push(Gene {
+                hm: genes.len() as u32,
+                enabled: true,
+                from,
+                to,
+                weight,
+            })
+        };
+        for (input, hidden) in [(3, 6), (1, 7)] {
+            if input == 1 && !source {
+                continue;
+            }
+            add_gene(input, hidden, 8.0);
+            add_gene(0, hidden, -1.0);
+            add_gene(hidden, 5, 1.0);
+        }
+        let mut cppn = Genome::from_genes(0, 4, 1, genes);
+        cppn.set_activation(5, Activation::Identity).unwrap();
+        cppn.set_activation(6, Activation::Gaussian).unwrap();
+        cppn.set_activation(7, Activation::Gaussian).unwrap();
+        cppn
+    }
+
+    #[test]
+    fn discovery() {
+        let inputs = vec![vec![-0.5, -1.0], vec![0.5, -1.0]];
+        let mut substrate = EvolvableSubstrate::new(inputs, vec![vec![0.0, 1.0]]).unwrap();
+        substrate.initial_depth = 3;
+        let mut network = substrate.build_network(&mut get_cppn(true)).unwrap();
+
+        // the points in the band, on the 8 rows of the grid
+        assert_eq!(network.get_n_hidden(), 8);
+        for node in &network.get_coordinates()[2..10] {
+            assert_eq!(node[0], 0.125);
+        }
+        // from each input, between every hidden node, and to the output
+        assert_eq!(network.get_n_links(), 2 * 8 + 8 * 8 + 8);
+        assert!(network
+            .get_links()
+            .iter()
+            .all(|(_, _, weight)| weight.abs() <= 3.0));
+        let outputs = network.compute(&[1.0, -1.0]).unwrap();
+        assert_eq!(outputs, network.clone().compute(&[1.0, -1.0]).unwrap());
+
+        // without the bands from the sources, no hidden node reaches the output
+        let mut network = substrate.build_network(&mut get_cppn(false)).unwrap();
+        assert_eq!(network.get_n_hidden(), 0);
+        assert_eq!(network.get_n_links(), 0);
+        assert_eq!(network.compute(&[1.0, -1.0]).unwrap(), vec![0.5]);
+
+        // a uniform CPPN has no bands
+        let network = substrate.build_network(&mut Genome::new(0, 4, 1)).unwrap();
+        assert_eq!(network.get_n_hidden(), 0);
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let mut substrate =
+            EvolvableSubstrate::new(vec![vec![0.0, -1.0]], vec![vec![0.0, 1.0]]).unwrap();
+        assert!(substrate.build_network(&mut Genome::new(0, 2, 1)).is_err());
+        substrate.initial_depth = 4;
+        assert!(substrate.build_network(&mut get_cppn(true)).is_err());
+        substrate.initial_depth = 2;
+        substrate.max_depth = MAX_DEPTH + 1;
+        assert!(substrate.build_network(&mut get_cppn(true)).is_err());
+        substrate.max_depth = 3;
+        substrate.iteration_level = MAX_ITERATION_LEVEL + 1;
+        assert!(substrate.build_network(&mut get_cppn(true)).is_err());
+        substrate.iteration_level = 1;
+        substrate.band_threshold = -1.0;
+        assert!(substrate.build_network(&mut get_cppn(true)).is_err());
+        assert!(EvolvableSubstrate::new(vec![vec![0.0]], vec![vec![0.0, 1.0]]).is_err());
+    }
+}
@@ -1,18 +1,25 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /**
-Gene is a structural mutation that happened to a given 
+Gene is a structural mutation that happened to a given
 */
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Gene {
     /**
     Historical Marking of the gene
     This is an indication of when the gene was created
     */
     pub hm: u32,
+    /// Disabled genes are kept in the genome but aren't part of the network
     pub enabled: bool,
+    /// Node the link comes from
     pub from: u32,
+    /// Node the link goes to
     pub to: u32,
+    /// Weight of the link
     pub weight: f64,
 }
 
@@ -1,22 +1,52 @@
 use core::fmt;
 
-use super::{Gene, LinkTo, Network, SETTINGS};
+use super::binary;
+use super::get_settings;
+use super::{
+    Activation, Activations, Gene, InnovationHistory, LinkTo, NeatError, Network, NodeType,
+};
+#[cfg(feature = "python")]
+use pyo3::types::PyBytes;
+#[cfg(feature = "python")]
 use pyo3::*;
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[pyclass]
+/**
+Genome of a network: its genes are the links between the nodes.
+The network is built from the genes on the first computation.
+*/
+#[cfg_attr(feature = "python", pyclass(module = "rust_neat"))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Genome {
+    /// Id of the genome, unique in a run
     pub id: u32,
+    /// Links of the network, in the order they were added
     pub genes: Vec<Gene>,
+    /// Number of nodes, the bias included
     pub n_nodes: u32,
+    /// Number of inputs, the bias excluded
     n_inputs: u32,
     n_outputs: u32,
+    /// Fitness given by the last evaluation
     pub fitness: f64,
+    /// Fitness shared with the other genomes of its species
     pub adj_fitness: f64,
-    pub network: Option<Network>,
+    /// Number of real-time ticks the genome lived through
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub age: u32,
+    /// Activation of the nodes that don't use the sigmoid
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    activations: BTreeMap<u32, Activation>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) network: Option<Network>,
 }
 
-static MAX_TRIES_MUTATIONS: i32 = 10;
-
 impl fmt::Display for Genome {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -27,18 +57,161 @@ impl fmt::Display for Genome {
     }
 }
 
+#[cfg(feature = "python")]
 #[pymethods]
 impl Genome {
+    #[getter(id)]
+    fn py_id(&self) -> u32 {
+        self.id
+    }
+
+    #[getter(fitness)]
+    fn py_fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    /// Fitness shared with the other genomes of its species
+    #[getter(adj_fitness)]
+    fn py_adj_fitness(&self) -> f64 {
+        self.adj_fitness
+    }
+
+    /// Creates a genome with every input linked to every output, all the weights being 0
+    #[new]
+    #[args(inputs, outputs)]
+    fn py_new(inputs: u32, outputs: u32) -> Result<Self, NeatError> {
+        if inputs == 0 || outputs == 0 {
+            return Err(NeatError::InvalidConfig(format!(
+                "networks need at least 1 input and 1 output, got {} inputs and {} outputs",
+                inputs, outputs
+            )));
+        }
+        Ok(Genome::new(0, inputs, outputs))
+    }
+
     fn __str__(&self) -> String {
         format!("{}", self)
     }
 
-    pub fn compute(&mut self, inputs: Vec<f64>) -> Vec<f64> {
-        self.build_network();
-        let network = self.get_network_mut();
-        network.set_inputs(inputs);
-        network.compute();
-        network.get_outputs()
+    /// The genome in the binary format, along with its adjusted fitness
+    fn __getstate__(&self, py: Python) -> (PyObject, f64) {
+        (self.py_to_bytes(py), self.adj_fitness)
+    }
+
+    fn __setstate__(&mut self, state: (&PyBytes, f64)) -> Result<(), NeatError> {
+        *self = Genome::from_bytes(state.0.as_bytes())?;
+        self.adj_fitness = state.1;
+        Ok(())
+    }
+
+    fn __reduce__(slf: &PyCell<Self>) -> (PyObject, (u32, u32), (PyObject, f64)) {
+        let genome = slf.borrow();
+        (
+            slf.get_type().into(),
+            (genome.n_inputs, genome.n_outputs),
+            genome.__getstate__(slf.py()),
+        )
+    }
+
+    #[pyo3(name = "compute")]
+    fn py_compute(&mut self, inputs: Vec<f64>) -> Result<Vec<f64>, NeatError> {
+        self.compute(inputs)
+    }
+
+    /// Returns the name of the activation of the node
+    #[pyo3(name = "get_activation")]
+    fn py_get_activation(&self, node: u32) -> &'static str {
+        self.get_activation(node).get_name()
+    }
+
+    /// Sets the activation of a hidden or output node: sigmoid, tanh, relu, identity, gaussian, sin or abs
+    #[pyo3(name = "set_activation")]
+    fn py_set_activation(&mut self, node: u32, activation: &str) -> Result<(), NeatError> {
+        self.set_activation(node, activation.parse()?)
+    }
+
+    /// Activations of the nodes that don't use the sigmoid, by node id
+    #[getter(activations)]
+    fn py_activations(&self) -> BTreeMap<u32, &'static str> {
+        self.activations
+            .iter()
+            .map(|(node, activation)| (*node, activation.get_name()))
+            .collect()
+    }
+
+    /// Returns the genome as JSON, the network isn't saved and is rebuilt on the next computation
+    #[cfg(feature = "serde")]
+    #[pyo3(name = "to_json")]
+    fn py_to_json(&self) -> Result<String, NeatError> {
+        self.to_json()
+    }
+
+    #[cfg(feature = "serde")]
+    #[staticmethod]
+    #[pyo3(name = "from_json")]
+    fn py_from_json(json: &str) -> Result<Self, NeatError> {
+        Genome::from_json(json)
+    }
+
+    /**
+    Returns the topology of the genome in the Graphviz DOT format,
+    disabled genes are drawn as dashed lines when `show_disabled` is true
+    */
+    #[args(show_disabled = "false")]
+    #[pyo3(name = "to_dot")]
+    fn py_to_dot(&self, show_disabled: bool) -> String {
+        self.to_dot(show_disabled)
+    }
+
+    /// Returns a self-contained SVG image of the network
+    #[pyo3(name = "to_svg")]
+    fn py_to_svg(&self) -> String {
+        self.to_svg()
+    }
+
+    #[pyo3(name = "save_svg")]
+    fn py_save_svg(&self, path: &str) -> Result<(), NeatError> {
+        self.save_svg(path)
+    }
+
+    /// Returns the network as a serialized ONNX model
+    #[pyo3(name = "to_onnx")]
+    fn py_to_onnx(&self, py: Python) -> PyObject {
+        PyBytes::new(py, &self.to_onnx()).into()
+    }
+
+    #[pyo3(name = "save_onnx")]
+    fn py_save_onnx(&self, path: &str) -> Result<(), NeatError> {
+        self.save_onnx(path)
+    }
+
+    /// Returns the source code of a function evaluating the network, `language` being "rust" or "c"
+    #[pyo3(name = "codegen")]
+    fn py_codegen(&self, language: &str) -> Result<String, NeatError> {
+        self.codegen(language.parse()?)
+    }
+
+    /// Returns the genome in the compact binary format
+    #[pyo3(name = "to_bytes")]
+    fn py_to_bytes(&self, py: Python) -> PyObject {
+        PyBytes::new(py, &self.to_bytes()).into()
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "from_bytes")]
+    fn py_from_bytes(data: &[u8]) -> Result<Self, NeatError> {
+        Genome::from_bytes(data)
+    }
+
+    #[pyo3(name = "save_binary")]
+    fn py_save_binary(&self, path: &str) -> Result<(), NeatError> {
+        self.save_binary(path)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "load_binary")]
+    fn py_load_binary(path: &str) -> Result<Self, NeatError> {
+        Genome::load_binary(path)
     }
 }
 
@@ -50,8 +223,10 @@ impl Clone for Genome {
             n_nodes: self.n_nodes,
             n_inputs: self.n_inputs,
             n_outputs: self.n_outputs,
-            fitness: 0.0,
-            adj_fitness: 0.0,
+            fitness: self.fitness,
+            adj_fitness: self.adj_fitness,
+            age: self.age,
+            activations: self.activations.clone(),
             network: self.network.clone(),
         }
     }
@@ -59,6 +234,7 @@ impl Clone for Genome {
 
 /// General genome functions
 impl Genome {
+    /// Creates a genome linking every input to every output
     pub fn new(id: u32, n_inputs: u32, n_outputs: u32) -> Self {
         Genome {
             id,
@@ -68,13 +244,48 @@ impl Genome {
             n_outputs,
             fitness: 0.0,
             adj_fitness: 0.0,
+            age: 0,
+            activations: BTreeMap::new(),
             network: None,
         }
         .build_genome()
     }
 
+    /// Creates a genome from existing genes
+    pub fn from_genes(id: u32, n_inputs: u32, n_outputs: u32, genes: Vec<Gene>) -> Self {
+        let mut genome = Genome {
+            id,
+            genes,
+            n_nodes: n_inputs + n_outputs + 1,
+            n_inputs,
+            n_outputs,
+            fitness: 0.0,
+            adj_fitness: 0.0,
+            age: 0,
+            activations: BTreeMap::new(),
+            network: None,
+        };
+        genome.n_nodes += genome.get_nb_hidden_nodes() as u32;
+        genome
+    }
+
+    /// Number of inputs, the bias excluded
+    pub fn get_n_inputs(&self) -> u32 {
+        self.n_inputs
+    }
+
+    /// Number of outputs
+    pub fn get_n_outputs(&self) -> u32 {
+        self.n_outputs
+    }
+
+    /// Links every input to every output, the outputs getting the activation given in the settings
     fn build_genome(mut self) -> Self {
         let mut historical_marking = 0;
+        let output_activation = get_settings().output_activation;
+        for output in self.n_inputs + 1..=self.n_inputs + self.n_outputs {
+            self.set_activation(output, output_activation).unwrap();
+        }
 
         // we start at 1 because 0 is the bias node
         for i in 1..=self.n_inputs {
@@ -94,7 +305,7 @@ impl Genome {
 
     fn build_network(&mut self) {
         if self.network.is_none() {
-            self.network = Some(Network::new(&self, self.n_inputs, self.n_outputs));
+            self.network = Some(Network::new(self, self.n_inputs, self.n_outputs));
             self.n_nodes = self
                 .network
                 .as_ref()
@@ -106,6 +317,129 @@ impl Genome {
         }
     }
 
+    /**
+    Checks that no gene goes to the bias or an input node, and that the enabled genes don't form
+    a cycle, the networks being feed-forward
+    */
+    pub fn check_genes(&self) -> Result<(), String> {
+        match self.genes.iter().find(|gene| gene.to <= self.n_inputs) {
+            Some(gene) => Err(format!(
+                "gene {} goes to node {}, which is the bias or an input",
+                gene.hm, gene.to
+            )),
+            None => Ok(()),
+        }?;
+        match self.activations.keys().find(|node| **node <= self.n_inputs) {
+            Some(node) => Err(format!(
+                "node {} has an activation but it is the bias or an input",
+                node
+            )),
+            None => Ok(()),
+        }?;
+        match self.find_cycle() {
+            Some(node) => Err(format!(
+                "the enabled genes form a cycle through node {}",
+                node
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns a node that is part of a cycle of enabled genes, if there is one
+    fn find_cycle(&self) -> Option<u32> {
+        let mut in_degrees: BTreeMap<u32, usize> = BTreeMap::new();
+        let mut successors: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for gene in self.genes.iter().filter(|gene| gene.enabled) {
+            in_degrees.entry(gene.from).or_insert(0);
+            *in_degrees.entry(gene.to).or_insert(0) += 1;
+            successors.entry(gene.from).or_default().push(gene.to);
+        }
+        // the nodes left after removing the ones without predecessors are all on or after a cycle
+        let mut ready: Vec<u32> = in_degrees
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(node, _)| *node)
+            .collect();
+        while let Some(node) = ready.pop() {
+            in_degrees.remove(&node);
+            for succ in successors.get(&node).into_iter().flatten() {
+                let degree = in_degrees.get_mut(succ).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push(*succ);
+                }
+            }
+        }
+        in_degrees.keys().next().copied()
+    }
+
+    /// Checks that the fitness and the weights of the genome are finite numbers
+    pub fn check_finite(&self) -> Result<(), String> {
+        if !self.fitness.is_finite() || !self.adj_fitness.is_finite() {
+            return Err(format!(
+                "the fitness of genome {} isn't a finite number",
+                self.id
+            ));
+        }
+        match self.genes.iter().find(|gene| !gene.weight.is_finite()) {
+            Some(gene) => Err(format!(
+                "the weight of gene {} of genome {} isn't a finite number",
+                gene.hm, self.id
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the number of hidden nodes used by the genes, enabled or not
+    pub fn get_nb_hidden_nodes(&self) -> usize {
+        let first_hidden = self.n_inputs + self.n_outputs + 1;
+        let mut hidden: Vec<u32> = self
+            .genes
+            .iter()
+            .flat_map(|gene| [gene.from, gene.to])
+            .filter(|node| *node >= first_hidden)
+            .collect();
+        hidden.sort_unstable();
+        hidden.dedup();
+        hidden.len()
+    }
+
+    /// Returns the number of links on the longest path from an input or the bias to an output
+    pub fn get_depth(&self) -> usize {
+        Network::new(self, self.n_inputs, self.n_outputs)
+            .get_depths()
+            .into_values()
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the activation of the node, the sigmoid unless it was changed
+    pub fn get_activation(&self, node: u32) -> Activation {
+        self.activations.get(&node).copied().unwrap_or_default()
+    }
+
+    /// Returns the activation of the nodes that don't use the sigmoid
+    pub fn get_activations(&self) -> &BTreeMap<u32, Activation> {
+        &self.activations
+    }
+
+    /// Sets the activation of a hidden or output node, the bias and the inputs don't have one
+    pub fn set_activation(&mut self, node: u32, activation: Activation) -> Result<(), NeatError> {
+        if node <= self.n_inputs {
+            return Err(NeatError::InvalidConfig(format!(
+                "node {} is the bias or an input, it has no activation",
+                node
+            )));
+        }
+        if activation == Activation::Sigmoid {
+            self.activations.remove(&node);
+        } else {
+            self.activations.insert(node, activation);
+        }
+        self.network = None;
+        Ok(())
+    }
+
     fn get_network_mut(&mut self) -> &mut Network {
         self.network.as_mut().unwrap()
     }
@@ -113,6 +447,63 @@ impl Genome {
     fn get_network(&self) -> &Network {
         self.network.as_ref().unwrap()
     }
+
+    /// Computes the outputs of the network, built from the genes on the first call
+    pub fn compute(&mut self, inputs: Vec<f64>) -> Result<Vec<f64>, NeatError> {
+        self.build_network();
+        let network = self.get_network_mut();
+        network.set_inputs(inputs)?;
+        network.compute();
+        Ok(network.get_outputs())
+    }
+}
+
+/// JSON serialization
+#[cfg(feature = "serde")]
+impl Genome {
+    /// Returns the genome as JSON, failing if its fitness or weights aren't finite as JSON can't represent them
+    pub fn to_json(&self) -> Result<String, NeatError> {
+        self.check_finite().map_err(NeatError::Json)?;
+        serde_json::to_string(self).map_err(|err| NeatError::Json(err.to_string()))
+    }
+
+    /// Reads a genome written by `to_json`, checking that its genes form a valid network
+    pub fn from_json(json: &str) -> Result<Self, NeatError> {
+        let genome: Genome =
+            serde_json::from_str(json).map_err(|err| NeatError::Json(err.to_string()))?;
+        genome.check_genes().map_err(NeatError::Json)?;
+        Ok(genome)
+    }
+}
+
+/// Binary serialization, see the `binary` module for the format
+impl Genome {
+    /// Returns the genome in the binary format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        binary::write_genomes(std::slice::from_ref(self))
+    }
+
+    /// Reads a genome written with `to_bytes`
+    pub fn from_bytes(data: &[u8]) -> Result<Self, NeatError> {
+        let mut genomes = binary::read_genomes(data)?;
+        if genomes.len() != 1 {
+            return Err(NeatError::InvalidFormat(format!(
+                "expected 1 genome, found {}",
+                genomes.len()
+            )));
+        }
+        Ok(genomes.remove(0))
+    }
+
+    /// Writes the genome to a file in the binary format
+    pub fn save_binary(&self, path: &str) -> Result<(), NeatError> {
+        Ok(std::fs::write(path, self.to_bytes())?)
+    }
+
+    /// Reads a genome written by `save_binary`
+    pub fn load_binary(path: &str) -> Result<Self, NeatError> {
+        Genome::from_bytes(&std::fs::read(path)?)
+    }
 }
 
 /// Mutate weights
@@ -121,16 +512,17 @@ impl Genome {
     Mutates the genome's weights.
     Always returns true
     */
-    pub fn mutate_weights(&mut self) -> bool {
+    pub fn mutate_weights(&mut self, rng: &mut impl Rng) -> bool {
         for gene in &mut self.genes {
-            let r: f64 = rand::random();
-            let w: f64 = (rand::random::<f64>() - 0.5) * 2.0; // w between -1 and 1
-            if r < unsafe { SETTINGS.w_mut_reassign } {
-                gene.weight = w * unsafe { SETTINGS.w_mut_reassign_max };
+            let r: f64 = rng.gen();
+            let w: f64 = (rng.gen::<f64>() - 0.5) * 2.0; // w between -1 and 1
+            if r < get_settings().w_mut_reassign {
+                gene.weight = w * get_settings().w_mut_reassign_max;
             } else {
-                gene.weight += w * unsafe { SETTINGS.w_mut_change_max };
+                gene.weight += w * get_settings().w_mut_change_max;
             }
         }
+        self.network = None;
         true
     }
 }
@@ -149,75 +541,265 @@ impl Genome {
             if node.0 < &(1 + self.n_inputs + self.n_outputs) {
                 continue;
             }
-            node_order -= 1;
             if node_order == 0 {
                 return *node.0;
             }
+            node_order -= 1;
         }
         node_order.try_into().unwrap_or(0)
     }
 
+    /// Returns true if a link can be added from `from` to `to`
+    fn is_linkable(&self, from: u32, to: u32) -> bool {
+        let nodes = &self.get_network().nodes;
+        // can't link from outputs, and links can't go back to a lower layer
+        nodes[&from].node_type != NodeType::OUTPUT
+            && from != to
+            && nodes[&to].layer >= i32::max(nodes[&from].layer, 1)
+            && !nodes[&from].succ.contains(&LinkTo { to })
+    }
+
+    /// Returns the number of nodes `from` can be linked to
+    fn get_nb_linkable_nodes(&self, from: u32) -> u32 {
+        self.get_network()
+            .nodes
+            .keys()
+            .filter(|to| self.is_linkable(from, **to))
+            .count() as u32
+    }
+
     /**
-    Returns two linkable nodes, or None if tries run out.
-    Call this function with `tries` as None.
+    Returns two nodes that can be linked, chosen at random,
+    or None if no link can be added.
     */
-    pub fn get_linkable_nodes(&mut self, tries: Option<i32>) -> Option<(u32, u32)> {
+    pub fn get_linkable_nodes(&mut self, rng: &mut impl Rng) -> Option<(u32, u32)> {
         self.build_network();
-        let tries = tries.unwrap_or(MAX_TRIES_MUTATIONS);
-        let mut from = rand::random::<u32>() % (self.n_nodes - self.n_outputs); // can't link from outputs
-        if from > self.n_inputs {
-            from += self.n_outputs;
-        }
-        from = self.get_nth_node(from);
-        let mut min_layer = self.get_network().nodes[&from].layer;
-        if min_layer == 0 {
-            min_layer = 1;
-        }
-        let mut nb_linkable_nodes = 0;
-
-        for ele in self.get_network().nodes.iter() {
-            if ele.1.layer >= min_layer
-                && !self.get_network().nodes[&from]
-                    .succ
-                    .contains(&LinkTo { to: *ele.0 })
-                && from != *ele.0
-            {
-                nb_linkable_nodes += 1;
+        let candidates: Vec<(u32, u32)> = self
+            .get_network()
+            .nodes
+            .keys()
+            .map(|from| (*from, self.get_nb_linkable_nodes(*from)))
+            .filter(|(_, nb_linkable_nodes)| *nb_linkable_nodes > 0)
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let (from, nb_linkable_nodes) = candidates[rng.gen_range(0..candidates.len())];
+        let pos_linkable_node = rng.gen_range(0..nb_linkable_nodes);
+        self.get_network()
+            .nodes
+            .keys()
+            .filter(|to| self.is_linkable(from, **to))
+            .nth(pos_linkable_node as usize)
+            .map(|to| (from, *to))
+    }
+
+    /// Returns the nodes of a random enabled gene, None if there isn't any
+    pub fn get_linked_nodes(&mut self, rng: &mut impl Rng) -> Option<(u32, u32)> {
+        if self.genes.is_empty() {
+            return None;
+        }
+        let gene_nb = rng.gen_range(0..self.genes.len());
+        for i in 0..self.genes.len() {
+            let gene = &self.genes[(gene_nb + i) % self.genes.len()];
+            if gene.enabled {
+                return Some((gene.from, gene.to));
             }
         }
+        None
+    }
+}
 
-        if nb_linkable_nodes == 0 {
-            return self.get_linkable_nodes(Some(tries - 1));
+/// Structural mutations
+impl Genome {
+    /**
+    Applies the mutations to the genome, each one with the chance given in the settings, the new nodes
+    and the activation mutations using `activations`.
+    The network is rebuilt on the next computation.
+    */
+    pub fn mutate(
+        &mut self,
+        activations: &Activations,
+        innovations: &mut InnovationHistory,
+        rng: &mut impl Rng,
+    ) {
+        if rng.gen::<f64>() < get_settings().w_mut_chance {
+            self.mutate_weights(rng);
+        }
+        if rng.gen::<f64>() < get_settings().add_node_chance {
+            self.add_node(activations.hidden, innovations, rng);
+        }
+        if rng.gen::<f64>() < get_settings().add_link_chance {
+            self.mutate_add_link(innovations, rng);
         }
+        // no random number is drawn without activation mutations, so that runs stay the same
+        let activation_mut_chance = activations.mutation_chance;
+        if activation_mut_chance > 0.0 && rng.gen::<f64>() < activation_mut_chance {
+            self.mutate_activation(rng);
+        }
+    }
 
-        let mut pos_linkable_node = rand::random::<u32>() % nb_linkable_nodes;
+    /**
+    Gives a random activation to a random hidden or output node.
+    Returns false if the genome has none
+    */
+    pub fn mutate_activation(&mut self, rng: &mut impl Rng) -> bool {
+        self.build_network();
+        let nodes: Vec<u32> = self
+            .get_network()
+            .nodes
+            .keys()
+            .copied()
+            .filter(|node| *node > self.n_inputs)
+            .collect();
+        if nodes.is_empty() {
+            return false;
+        }
+        let node = nodes[rng.gen_range(0..nodes.len())];
+        let activation = Activation::ALL[rng.gen_range(0..Activation::ALL.len())];
+        self.set_activation(node, activation).unwrap();
+        true
+    }
 
-        for ele in self.get_network().nodes.iter() {
-            if ele.1.layer >= min_layer
-                && !self.get_network().nodes[&from]
-                    .succ
-                    .contains(&LinkTo { to: *ele.0 })
-                && from != *ele.0
-            {
-                if pos_linkable_node == 0 {
-                    return Some((from, *ele.0));
-                }
-                pos_linkable_node -= 1;
-            }
+    /**
+    Adds a link between two nodes that weren't linked.
+    Returns false if no link could be found
+    */
+    pub fn mutate_add_link(
+        &mut self,
+        innovations: &mut InnovationHistory,
+        rng: &mut impl Rng,
+    ) -> bool {
+        let (from, to) = match self.get_linkable_nodes(rng) {
+            Some(nodes) => nodes,
+            None => return false,
+        };
+        let weight = (rng.gen::<f64>() - 0.5) * 2.0 * get_settings().w_mut_reassign_max;
+
+        // the link may already exist as a disabled gene
+        if let Some(gene) = self
+            .genes
+            .iter_mut()
+            .find(|gene| gene.from == from && gene.to == to)
+        {
+            gene.enabled = true;
+            gene.weight = weight;
+        } else {
+            self.insert_gene(Gene {
+                enabled: true,
+                from,
+                to,
+                hm: innovations.get_link_hm(from, to),
+                weight,
+            });
         }
+        self.network = None;
+        true
+    }
 
-        return self.get_linkable_nodes(Some(tries - 1));
+    /**
+    Splits an enabled gene in two by adding a node in the middle, with the hidden activation given in
+    the settings.
+    The link going to the new node has a weight of 1, the one leaving it keeps the old weight.
+    Returns false if the genome has no enabled gene
+    */
+    pub fn mutate_add_node(
+        &mut self,
+        innovations: &mut InnovationHistory,
+        rng: &mut impl Rng,
+    ) -> bool {
+        self.add_node(get_settings().hidden_activation, innovations, rng)
     }
 
-    pub fn get_linked_nodes(&mut self) -> Option<(u32, u32)> {
-        let gene_nb = rand::random::<usize>() % self.genes.len();
-        for i in 0..self.genes.len() {
-            let gene = &self.genes[(gene_nb + i) % self.genes.len()];
-            if gene.enabled == true {
-                return Some((gene.from, gene.to));
+    /// Same as `mutate_add_node`, the new node getting `activation`
+    fn add_node(
+        &mut self,
+        activation: Activation,
+        innovations: &mut InnovationHistory,
+        rng: &mut impl Rng,
+    ) -> bool {
+        let (from, to) = match self.get_linked_nodes(rng) {
+            Some(nodes) => nodes,
+            None => return false,
+        };
+        let gene = self
+            .genes
+            .iter_mut()
+            .find(|gene| gene.enabled && gene.from == from && gene.to == to)
+            .unwrap();
+        gene.enabled = false;
+        let (hm, weight) = (gene.hm, gene.weight);
+
+        let mut node = innovations.get_split_node(hm);
+        // the gene was already split once in this genome
+        if self
+            .genes
+            .iter()
+            .any(|gene| gene.from == node || gene.to == node)
+        {
+            node = innovations.get_new_node();
+        }
+
+        self.insert_gene(Gene {
+            enabled: true,
+            from,
+            to: node,
+            hm: innovations.get_link_hm(from, node),
+            weight: 1.0,
+        });
+        self.insert_gene(Gene {
+            enabled: true,
+            from: node,
+            to,
+            hm: innovations.get_link_hm(node, to),
+            weight,
+        });
+        self.set_activation(node, activation).unwrap();
+        self.n_nodes += 1;
+        self.network = None;
+        true
+    }
+
+    /// Inserts the gene while keeping the genes sorted by historical marking
+    fn insert_gene(&mut self, gene: Gene) {
+        let index = self.genes.partition_point(|other| other.hm < gene.hm);
+        self.genes.insert(index, gene);
+    }
+}
+
+/// Crossover
+impl Genome {
+    /**
+    Creates a child from two parents.
+    The child has the structure of the fittest parent, matching genes get their weight from a random parent.
+    */
+    pub fn crossover(id: u32, fittest: &Self, other: &Self, rng: &mut impl Rng) -> Self {
+        let mut child = Genome {
+            id,
+            genes: Vec::with_capacity(fittest.genes.len()),
+            n_nodes: fittest.n_nodes,
+            n_inputs: fittest.n_inputs,
+            n_outputs: fittest.n_outputs,
+            fitness: 0.0,
+            adj_fitness: 0.0,
+            age: 0,
+            activations: fittest.activations.clone(),
+            network: None,
+        };
+
+        let mut j = 0;
+        for gene in &fittest.genes {
+            while j < other.genes.len() && other.genes[j].hm < gene.hm {
+                j += 1;
+            }
+            let mut gene = gene.clone();
+            if j < other.genes.len() && other.genes[j].hm == gene.hm && rng.gen::<bool>() {
+                gene.weight = other.genes[j].weight;
             }
+            child.genes.push(gene);
         }
-        None
+        child
     }
 }
 
@@ -255,7 +837,7 @@ impl Genome {
 
     fn get_n(a: &Self, b: &Self) -> u32 {
         let n = std::cmp::max(a.n_nodes, b.n_nodes);
-        if n <= unsafe { SETTINGS.small_genome_size } {
+        if n <= get_settings().small_genome_size {
             1
         } else {
             n
@@ -310,22 +892,23 @@ impl Genome {
         (w_diff, disjoint, excess)
     }
 
+    /// Returns the compatibility distance between two genomes, used to group them in species
     pub fn compute_difference(a: &Self, b: &Self) -> f64 {
         let n = Genome::get_n(a, b);
         let (weight_diff, disjoint, excess) = Genome::get_differnce_values(a, b);
 
-        unsafe {
-            (SETTINGS.similarity_c1 * f64::from(excess)
-                + SETTINGS.similarity_c2 * f64::from(disjoint))
-                / f64::from(n)
-                + SETTINGS.similarity_c3 * weight_diff
-        }
+        let settings = get_settings();
+        (settings.similarity_c1 * f64::from(excess) + settings.similarity_c2 * f64::from(disjoint))
+            / f64::from(n)
+            + settings.similarity_c3 * weight_diff
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
 
     #[test]
     fn check_genes_on_build() {
@@ -341,6 +924,25 @@ mod tests {
         assert_eq!(genome.n_nodes, 5 + 6 + 1);
     }
 
+    #[test]
+    fn check_genes_cycles() {
+        let gene = |from, to, hm| Gene {
+            enabled: true,
+            from,
+            to,
+            hm,
+            weight: 1.0,
+        };
+        let mut genome =
+            Genome::from_genes(0, 1, 1, vec![gene(1, 3, 0), gene(3, 4, 1), gene(4, 3, 2)]);
+        assert!(genome.check_genes().unwrap_err().contains("cycle"));
+        genome.genes[2].enabled = false;
+        assert!(genome.check_genes().is_ok());
+
+        let genome = Genome::from_genes(0, 1, 1, vec![gene(1, 3, 0), gene(3, 3, 1)]);
+        assert!(genome.check_genes().is_err());
+    }
+
     #[test]
     fn compute_difference() {
         let mut g1 = Genome::new(0, 2, 2);
@@ -349,9 +951,10 @@ mod tests {
         assert_eq!(Genome::compute_difference(&g1, &g2), 0.0);
 
         g2.genes[0].weight += 1.0;
-        assert_eq!(Genome::compute_difference(&g1, &g2), unsafe {
-            SETTINGS.similarity_c3
-        });
+        assert_eq!(
+            Genome::compute_difference(&g1, &g2),
+            get_settings().similarity_c3
+        );
 
         g2.genes[0].weight -= 1.0;
         g2.genes.push(Gene {
@@ -361,9 +964,10 @@ mod tests {
             hm: 100,
             weight: 1.0,
         });
-        assert_eq!(Genome::compute_difference(&g1, &g2), unsafe {
-            SETTINGS.similarity_c1
-        });
+        assert_eq!(
+            Genome::compute_difference(&g1, &g2),
+            get_settings().similarity_c1
+        );
 
         g1.genes.push(Gene {
             enabled: true,
@@ -372,16 +976,18 @@ mod tests {
             hm: 99,
             weight: 1.0,
         });
-        assert_eq!(Genome::compute_difference(&g1, &g2), unsafe {
-            SETTINGS.similarity_c1 + SETTINGS.similarity_c2
+        assert_eq!(Genome::compute_difference(&g1, &g2), {
+            let settings = get_settings();
+            settings.similarity_c1 + settings.similarity_c2
         });
     }
 
     #[test]
     fn get_linkable_nodes_basic() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
         let mut g1 = Genome::new(0, 5, 5);
 
-        let nodes = g1.get_linkable_nodes(None);
+        let nodes = g1.get_linkable_nodes(&mut rng);
         assert!(nodes.is_some());
         let nodes = nodes.unwrap();
         assert!(nodes.0 != nodes.1);
@@ -389,35 +995,195 @@ mod tests {
     }
 
     #[test]
-    fn get_linkable_nodes_advanced() {
-        for _ in 0..10 {
-            let mut g1 = Genome::new(0, 5, 5);
+    fn mutate_add_node() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut history = InnovationHistory::new(2, 1);
+        let mut g1 = Genome::new(0, 2, 1);
 
-            for i in 117..200 {
-                g1.genes.push(Gene {
-                    enabled: true,
-                    from: 0,
-                    to: i,
-                    hm: i,
-                    weight: 0.1,
-                });
+        assert_eq!(g1.get_nb_hidden_nodes(), 0);
+        assert!(g1.mutate_add_node(&mut history, &mut rng));
+        assert_eq!(g1.get_nb_hidden_nodes(), 1);
+        assert_eq!(g1.genes.len(), 4);
+        assert_eq!(g1.genes.iter().filter(|gene| !gene.enabled).count(), 1);
+        assert_eq!(g1.compute(vec![1.0, 1.0]).unwrap().len(), 1);
+        assert_eq!(g1.n_nodes, 5);
+        for i in 1..g1.genes.len() {
+            assert!(g1.genes[i - 1].hm < g1.genes[i].hm);
+        }
+    }
 
-                g1.genes.push(Gene {
-                    enabled: true,
-                    from: i,
-                    to: 7,
-                    hm: i * 2,
-                    weight: 0.1,
-                });
-            }
+    #[test]
+    fn get_nth_node() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut history = InnovationHistory::new(2, 1);
+        let mut g1 = Genome::new(0, 2, 1);
+        g1.mutate_add_node(&mut history, &mut rng);
+        g1.mutate_add_node(&mut history, &mut rng);
+        g1.compute(vec![1.0, 1.0]).unwrap();
+
+        assert_eq!(g1.get_nth_node(3), 3);
+        // the hidden nodes come after the bias, the inputs and the output
+        assert_eq!(g1.get_nth_node(4), 4);
+        assert_eq!(g1.get_nth_node(5), 5);
+    }
+
+    #[test]
+    fn mutate_add_link() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut history = InnovationHistory::new(2, 1);
+        let mut g1 = Genome::new(0, 2, 1);
+
+        // only the bias isn't linked to the output
+        assert!(g1.mutate_add_link(&mut history, &mut rng));
+        assert_eq!(g1.genes.len(), 3);
+        assert_eq!(g1.genes[2].from, 0);
+        assert_eq!(g1.genes[2].to, 3);
+        assert!(!g1.mutate_add_link(&mut history, &mut rng));
+    }
+
+    #[test]
+    fn crossover_keeps_fittest_structure() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut history = InnovationHistory::new(2, 2);
+        let g1 = Genome::new(0, 2, 2);
+        let mut g2 = Genome::new(1, 2, 2);
+        g2.mutate_add_node(&mut history, &mut rng);
+
+        let child = Genome::crossover(2, &g2, &g1, &mut rng);
+        assert_eq!(child.id, 2);
+        assert_eq!(child.genes.len(), g2.genes.len());
+        assert_eq!(Genome::compute_difference(&child, &g2), 0.0);
+    }
+
+    #[test]
+    fn activations() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut genome = Genome::new(0, 2, 1);
+        genome.genes[0].weight = -1.0;
+        assert_eq!(genome.get_activation(3), Activation::Sigmoid);
+        assert!(genome.set_activation(2, Activation::Relu).is_err());
+
+        genome.compute(vec![1.0, 0.0]).unwrap();
+        genome.set_activation(3, Activation::Relu).unwrap();
+        assert_eq!(genome.compute(vec![1.0, 0.0]).unwrap(), vec![0.0]);
+        genome.set_activation(3, Activation::Abs).unwrap();
+        assert_eq!(genome.compute(vec![1.0, 0.0]).unwrap(), vec![1.0]);
+        // the child takes the activations of the fittest parent
+        let child = Genome::crossover(1, &genome, &Genome::new(2, 2, 1), &mut rng);
+        assert_eq!(child.get_activation(3), Activation::Abs);
+        genome.set_activation(3, Activation::Sigmoid).unwrap();
+        assert!(genome.get_activations().is_empty());
+
+        while genome.get_activations().is_empty() {
+            assert!(genome.mutate_activation(&mut rng));
+        }
+        assert!(genome.get_activations().contains_key(&3));
+        // only the bias and the inputs, which have no activation
+        assert!(!Genome::new(1, 2, 0).mutate_activation(&mut rng));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut history = InnovationHistory::new(3, 2);
+        let mut g1 = Genome::new(7, 3, 2);
+        g1.mutate_weights(&mut rng);
+        for _ in 0..5 {
+            g1.mutate_add_node(&mut history, &mut rng);
+            g1.mutate_add_link(&mut history, &mut rng);
+            g1.mutate_weights(&mut rng);
+        }
+        g1.fitness = 1.5;
+        g1.set_activation(4, Activation::Sin).unwrap();
 
-            let nodes = g1.get_linkable_nodes(None);
-            assert!(nodes.is_some());
-            let nodes = nodes.unwrap();
-            assert!(nodes.0 != nodes.1);
+        let mut g2 = Genome::from_json(&g1.to_json().unwrap()).unwrap();
+        assert_eq!(g2.id, 7);
+        assert_eq!(g2.fitness, 1.5);
+        assert_eq!(g2.genes.len(), g1.genes.len());
+        assert_eq!(g2.get_activation(4), Activation::Sin);
+        for inputs in [[0.0, 0.0, 0.0], [1.0, -0.5, 0.25], [0.3, 0.7, -2.0]] {
+            assert_eq!(
+                g1.compute(inputs.to_vec()).unwrap(),
+                g2.compute(inputs.to_vec()).unwrap()
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_invalid() {
+        assert!(matches!(
+            Genome::from_json("{\"id\": 0}"),
+            Err(NeatError::Json(_))
+        ));
+
+        let mut g1 = Genome::new(0, 2, 1);
+        g1.genes[0].to = 1;
+        assert!(matches!(
+            Genome::from_json(&g1.to_json().unwrap()),
+            Err(NeatError::Json(_))
+        ));
+
+        let mut g1 = Genome::new(0, 2, 1);
+        g1.fitness = f64::NEG_INFINITY;
+        assert!(matches!(g1.to_json(), Err(NeatError::Json(_))));
+        g1.fitness = 0.0;
+        g1.genes[1].weight = f64::NAN;
+        assert!(matches!(g1.to_json(), Err(NeatError::Json(_))));
+
+        // a cycle would make the first computation panic
+        let mut g1 = Genome::new(0, 2, 1);
+        for (from, to, hm) in [(3, 4, 2), (4, 3, 3)] {
+            g1.genes.push(Gene {
+                enabled: true,
+                from,
+                to,
+                hm,
+                weight: 1.0,
+            });
+        }
+        assert!(matches!(
+            Genome::from_json(&g1.to_json().unwrap()),
+            Err(NeatError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn get_linkable_nodes_advanced() {
+        let mut g1 = Genome::new(0, 5, 5);
+        for i in 117..200 {
+            g1.genes.push(Gene {
+                enabled: true,
+                from: 0,
+                to: i,
+                hm: i,
+                weight: 0.1,
+            });
+
+            g1.genes.push(Gene {
+                enabled: true,
+                from: i,
+                to: 7,
+                hm: i * 2,
+                weight: 0.1,
+            });
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for _ in 0..100 {
+            let nodes = g1.get_linkable_nodes(&mut rng).unwrap();
+            assert!(g1.is_linkable(nodes.0, nodes.1));
             let layer1 = g1.network.as_ref().unwrap().nodes[&nodes.0].layer;
             let layer2 = g1.network.as_ref().unwrap().nodes[&nodes.1].layer;
             assert!(layer1 <= layer2);
         }
+
+        // the same seed gives the same nodes
+        let mut a = ChaCha8Rng::seed_from_u64(1);
+        let mut b = ChaCha8Rng::seed_from_u64(1);
+        for _ in 0..10 {
+            assert_eq!(g1.get_linkable_nodes(&mut a), g1.get_linkable_nodes(&mut b));
+        }
     }
 }
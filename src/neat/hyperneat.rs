@@ -0,0 +1,580 @@
+/*!
+HyperNEAT: the evolved genomes are CPPNs (compositional pattern producing networks) drawing the weights
+of a larger network, whose nodes are placed at fixed coordinates on a substrate.
+
+The CPPN takes the coordinates of the source and of the target of a link, and returns its weight.
+Links whose weight is below the threshold in absolute value aren't expressed, the others are scaled to
+`[-max_weight, max_weight]`. If the CPPN has a second output, it gives the bias of each node, queried with
+the source at the origin. CPPNs usually give their hidden nodes periodic and symmetric activations
+(`Activation::Sin`, `Activation::Gaussian`, `Activation::Abs`) to draw regular patterns, and use
+`Activation::Tanh` or `Activation::Identity` for their outputs so that the weights can be negative.
+*/
+
+use super::{Activation, Genome, NeatError};
+#[cfg(feature = "python")]
+use pyo3::*;
+use std::collections::BTreeSet;
+
+/// Nodes of a substrate network, placed by their coordinates
+#[cfg_attr(feature = "python", pyclass(module = "rust_neat"))]
+#[derive(Clone, Debug)]
+pub struct Substrate {
+    inputs: Vec<Vec<f64>>,
+    hidden: Vec<Vec<Vec<f64>>>,
+    outputs: Vec<Vec<f64>>,
+    /// Links whose CPPN output is below this in absolute value aren't expressed, 0.2 by default
+    pub weight_threshold: f64,
+    /// Largest weight of the links, 3.0 by default
+    pub max_weight: f64,
+    /// Activation of the hidden and output nodes of the networks
+    pub activation: Activation,
+}
+
+impl Substrate {
+    /**
+    Creates a substrate from the coordinates of its nodes, the hidden nodes being given by layer.
+    Every layer is linked to the next one, the inputs to the first hidden layer and the last one to
+    the outputs. Every node must have the same number of coordinates.
+    */
+    pub fn new(
+        inputs: Vec<Vec<f64>>,
+        hidden: Vec<Vec<Vec<f64>>>,
+        outputs: Vec<Vec<f64>>,
+    ) -> Result<Self, NeatError> {
+        if inputs.is_empty() || outputs.is_empty() {
+            return Err(NeatError::InvalidConfig(format!(
+                "substrates need at least 1 input and 1 output, got {} inputs and {} outputs",
+                inputs.len(),
+                outputs.len()
+            )));
+        }
+        let dimensions = inputs[0].len();
+        if dimensions == 0 {
+            return Err(NeatError::InvalidConfig(String::from(
+                "the nodes of the substrate need coordinates",
+            )));
+        }
+        if let Some(node) = inputs
+            .iter()
+            .chain(hidden.iter().flatten())
+            .chain(&outputs)
+            .find(|node| node.len() != dimensions)
+        {
+            return Err(NeatError::InvalidConfig(format!(
+                "node {:?} has {} coordinates, expected {}",
+                node,
+                node.len(),
+                dimensions
+            )));
+        }
+        Ok(Substrate {
+            inputs,
+            hidden: hidden
+                .into_iter()
+                .filter(|layer| !layer.is_empty())
+                .collect(),
+            outputs,
+            weight_threshold: 0.2,
+            max_weight: 3.0,
+            activation: Activation::Sigmoid,
+        })
+    }
+
+    /// Coordinates of the input nodes
+    pub fn get_inputs(&self) -> &[Vec<f64>] {
+        &self.inputs
+    }
+
+    /// Coordinates of the hidden nodes, layer by layer
+    pub fn get_hidden(&self) -> &[Vec<Vec<f64>>] {
+        &self.hidden
+    }
+
+    /// Coordinates of the output nodes
+    pub fn get_outputs(&self) -> &[Vec<f64>] {
+        &self.outputs
+    }
+
+    /// Number of coordinates of each node
+    pub fn get_dimensions(&self) -> usize {
+        self.inputs[0].len()
+    }
+
+    /// Number of inputs the CPPNs need: the coordinates of the source and of the target
+    pub fn get_cppn_inputs(&self) -> usize {
+        2 * self.get_dimensions()
+    }
+
+    /// Checks that the CPPN can be queried for this substrate, and the parameters of the substrate
+    pub(crate) fn check_cppn(&self, cppn: &Genome) -> Result<(), NeatError> {
+        if cppn.get_n_inputs() as usize != self.get_cppn_inputs()
+            || !(1..=2).contains(&cppn.get_n_outputs())
+        {
+            return Err(NeatError::InvalidConfig(format!(
+                "the CPPN has {} inputs and {} outputs, expected {} inputs and 1 or 2 outputs",
+                cppn.get_n_inputs(),
+                cppn.get_n_outputs(),
+                self.get_cppn_inputs()
+            )));
+        }
+        if !(0.0..1.0).contains(&self.weight_threshold) {
+            return Err(NeatError::InvalidConfig(format!(
+                "the weight threshold must be in [0, 1), got {}",
+                self.weight_threshold
+            )));
+        }
+        if self.max_weight.is_nan() || self.max_weight <= 0.0 {
+            return Err(NeatError::InvalidConfig(format!(
+                "the max weight must be positive, got {}",
+                self.max_weight
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the weight given by the CPPN output, none if the link isn't expressed
+    pub(crate) fn get_weight(&self, output: f64) -> Option<f64> {
+        let magnitude = output.abs().min(1.0);
+        if magnitude.is_nan() || magnitude <= self.weight_threshold {
+            return None;
+        }
+        let weight =
+            (magnitude - self.weight_threshold) / (1.0 - self.weight_threshold) * self.max_weight;
+        Some(weight.copysign(output))
+    }
+
+    /// Queries the CPPN for the link between two nodes, returns its weight and the bias output
+    pub(crate) fn query(
+        cppn: &mut Genome,
+        source: &[f64],
+        target: &[f64],
+    ) -> Result<(f64, Option<f64>), NeatError> {
+        let outputs = cppn.compute(source.iter().chain(target).copied().collect())?;
+        Ok((outputs[0], outputs.get(1).copied()))
+    }
+
+    /// Returns the biases of the nodes, given by the second output of the CPPN if it has one
+    pub(crate) fn get_biases(
+        &self,
+        cppn: &mut Genome,
+        nodes: &[Vec<f64>],
+    ) -> Result<Vec<f64>, NeatError> {
+        let origin = vec![0.0; self.get_dimensions()];
+        nodes
+            .iter()
+            .map(|node| match Substrate::query(cppn, &origin, node)?.1 {
+                Some(output) => Ok(self.get_weight(output).unwrap_or(0.0)),
+                None => Ok(0.0),
+            })
+            .collect()
+    }
+
+    /// Builds the network whose weights are given by the CPPN
+    pub fn build_network(&self, cppn: &mut Genome) -> Result<SubstrateNetwork, NeatError> {
+        self.check_cppn(cppn)?;
+        let layers: Vec<&[Vec<f64>]> = std::iter::once(self.inputs.as_slice())
+            .chain(self.hidden.iter().map(Vec::as_slice))
+            .chain(std::iter::once(self.outputs.as_slice()))
+            .collect();
+
+        let mut links = Vec::new();
+        let mut first = 0;
+        for pair in layers.windows(2) {
+            let (sources, targets) = (pair[0], pair[1]);
+            for (target_index, target) in targets.iter().enumerate() {
+                for (source_index, source) in sources.iter().enumerate() {
+                    let (output, _) = Substrate::query(cppn, source, target)?;
+                    if let Some(weight) = self.get_weight(output) {
+                        let target_index = first + sources.len() + target_index;
+                        links.push((first + source_index, target_index, weight));
+                    }
+                }
+            }
+            first += sources.len();
+        }
+
+        let coordinates: Vec<Vec<f64>> = layers.concat();
+        let bias = self.get_biases(cppn, &coordinates[self.inputs.len()..])?;
+        SubstrateNetwork::new(
+            coordinates,
+            self.inputs.len(),
+            self.outputs.len(),
+            bias,
+            links,
+            self.activation,
+        )
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Substrate {
+    /**
+    Creates a substrate from the coordinates of its nodes, the hidden nodes being given by layer.
+    `activation` is the activation of the hidden and output nodes of the networks
+    */
+    #[new]
+    #[args(
+        inputs,
+        outputs,
+        hidden = "Vec::new()",
+        weight_threshold = "0.2",
+        max_weight = "3.0",
+        activation = "\"sigmoid\""
+    )]
+    fn py_new(
+        inputs: Vec<Vec<f64>>,
+        outputs: Vec<Vec<f64>>,
+        hidden: Vec<Vec<Vec<f64>>>,
+        weight_threshold: f64,
+        max_weight: f64,
+        activation: &str,
+    ) -> Result<Self, NeatError> {
+        let mut substrate = Substrate::new(inputs, hidden, outputs)?;
+        substrate.weight_threshold = weight_threshold;
+        substrate.max_weight = max_weight;
+        substrate.activation = activation.parse()?;
+        Ok(substrate)
+    }
+
+    #[getter(inputs)]
+    fn py_inputs(&self) -> Vec<Vec<f64>> {
+        self.inputs.clone()
+    }
+
+    #[getter(hidden)]
+    fn py_hidden(&self) -> Vec<Vec<Vec<f64>>> {
+        self.hidden.clone()
+    }
+
+    #[getter(outputs)]
+    fn py_outputs(&self) -> Vec<Vec<f64>> {
+        self.outputs.clone()
+    }
+
+    /// Number of inputs the CPPNs need: the coordinates of the source and of the target
+    #[getter(cppn_inputs)]
+    fn py_cppn_inputs(&self) -> usize {
+        self.get_cppn_inputs()
+    }
+
+    /// Builds the network whose weights are given by the CPPN
+    #[pyo3(name = "build_network")]
+    fn py_build_network(&self, mut cppn: PyRefMut<Genome>) -> Result<SubstrateNetwork, NeatError> {
+        self.build_network(&mut cppn)
+    }
+}
+
+/**
+Network built from a substrate.
+The nodes are computed in topological order, so a single computation is enough when there are no
+cycles. The nodes keep their value between computations, the links closing a cycle use the value of
+the previous computation.
+*/
+#[cfg_attr(feature = "python", pyclass(module = "rust_neat"))]
+#[derive(Clone, Debug)]
+pub struct SubstrateNetwork {
+    /// Coordinates of the nodes: the inputs first, then the hidden nodes, then the outputs
+    coordinates: Vec<Vec<f64>>,
+    n_inputs: usize,
+    n_outputs: usize,
+    /// Bias of every node other than the inputs
+    bias: Vec<f64>,
+    /// Links going to every node other than the inputs, with their source and weight
+    pred: Vec<Vec<(usize, f64)>>,
+    activation: Activation,
+    /// Order in which the nodes other than the inputs are computed
+    order: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl SubstrateNetwork {
+    /**
+    Creates a network from its nodes, the inputs being first and the outputs last, and from its links
+    given as (source, target, weight) with the indices of the nodes.
+    `bias` holds the bias of every node other than the inputs.
+    */
+    pub fn new(
+        coordinates: Vec<Vec<f64>>,
+        n_inputs: usize,
+        n_outputs: usize,
+        bias: Vec<f64>,
+        links: Vec<(usize, usize, f64)>,
+        activation: Activation,
+    ) -> Result<Self, NeatError> {
+        let n_nodes = coordinates.len();
+        if n_inputs + n_outputs > n_nodes {
+            return Err(NeatError::InvalidConfig(format!(
+                "{} inputs and {} outputs don't fit in {} nodes",
+                n_inputs, n_outputs, n_nodes
+            )));
+        }
+        if bias.len() != n_nodes - n_inputs {
+            return Err(NeatError::InvalidConfig(format!(
+                "expected {} biases, one per node other than the inputs, got {}",
+                n_nodes - n_inputs,
+                bias.len()
+            )));
+        }
+        let mut pred = vec![Vec::new(); n_nodes - n_inputs];
+        for (from, to, weight) in links {
+            if from >= n_nodes || to < n_inputs || to >= n_nodes {
+                return Err(NeatError::InvalidConfig(format!(
+                    "invalid link {} -> {}, the network has {} nodes and {} inputs",
+                    from, to, n_nodes, n_inputs
+                )));
+            }
+            pred[to - n_inputs].push((from, weight));
+        }
+
+        // Kahn's algorithm, the nodes left in cycles are computed last
+        let mut n_pred: Vec<usize> = pred
+            .iter()
+            .enumerate()
+            .map(|(node, pred)| {
+                pred.iter()
+                    .filter(|(from, _)| *from >= n_inputs && *from != node + n_inputs)
+                    .count()
+            })
+            .collect();
+        let mut succ = vec![Vec::new(); n_nodes - n_inputs];
+        for (node, pred) in pred.iter().enumerate() {
+            for (from, _) in pred {
+                if *from >= n_inputs && *from != node + n_inputs {
+                    succ[from - n_inputs].push(node);
+                }
+            }
+        }
+        let mut ready: BTreeSet<usize> =
+            (0..pred.len()).filter(|node| n_pred[*node] == 0).collect();
+        let mut order = Vec::with_capacity(pred.len());
+        let mut done = vec![false; pred.len()];
+        while let Some(node) = ready.pop_first() {
+            order.push(node + n_inputs);
+            done[node] = true;
+            for next in &succ[node] {
+                n_pred[*next] -= 1;
+                if n_pred[*next] == 0 {
+                    ready.insert(*next);
+                }
+            }
+        }
+        order.extend(
+            (0..pred.len())
+                .filter(|node| !done[*node])
+                .map(|node| node + n_inputs),
+        );
+
+        Ok(SubstrateNetwork {
+            coordinates,
+            n_inputs,
+            n_outputs,
+            bias,
+            pred,
+            activation,
+            order,
+            values: vec![0.0; n_nodes],
+        })
+    }
+
+    /// Number of input nodes
+    pub fn get_n_inputs(&self) -> usize {
+        self.n_inputs
+    }
+
+    /// Number of output nodes
+    pub fn get_n_outputs(&self) -> usize {
+        self.n_outputs
+    }
+
+    /// Coordinates of the nodes: the inputs first, then the hidden nodes, then the outputs
+    pub fn get_coordinates(&self) -> &[Vec<f64>] {
+        &self.coordinates
+    }
+
+    /// Number of hidden nodes that are part of the network
+    pub fn get_n_hidden(&self) -> usize {
+        self.coordinates.len() - self.n_inputs - self.n_outputs
+    }
+
+    /// Returns the links as (source, target, weight), with the indices of the nodes
+    pub fn get_links(&self) -> Vec<(usize, usize, f64)> {
+        self.pred
+            .iter()
+            .enumerate()
+            .flat_map(|(node, pred)| {
+                pred.iter()
+                    .map(move |(from, weight)| (*from, node + self.n_inputs, *weight))
+            })
+            .collect()
+    }
+
+    /// Number of links of the network
+    pub fn get_n_links(&self) -> usize {
+        self.pred.iter().map(Vec::len).sum()
+    }
+
+    /// Computes the outputs of the network, the nodes being computed in topological order
+    pub fn compute(&mut self, inputs: &[f64]) -> Result<Vec<f64>, NeatError> {
+        if inputs.len() != self.n_inputs {
+            return Err(NeatError::WrongInputLength {
+                expected: self.n_inputs,
+                got: inputs.len(),
+            });
+        }
+        self.values[..self.n_inputs].copy_from_slice(inputs);
+        for node in &self.order {
+            let index = node - self.n_inputs;
+            let sum = self.pred[index]
+                .iter()
+                .fold(self.bias[index], |sum, (from, weight)| {
+                    sum + self.values[*from] * weight
+                });
+            self.values[*node] = self.activation.apply(sum);
+        }
+        Ok(self.values[self.values.len() - self.n_outputs..].to_vec())
+    }
+
+    /// Sets the values of the nodes back to 0
+    pub fn reset(&mut self) {
+        self.values.iter_mut().for_each(|value| *value = 0.0);
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SubstrateNetwork {
+    #[pyo3(name = "compute")]
+    fn py_compute(&mut self, inputs: Vec<f64>) -> Result<Vec<f64>, NeatError> {
+        self.compute(&inputs)
+    }
+
+    #[pyo3(name = "reset")]
+    fn py_reset(&mut self) {
+        self.reset()
+    }
+
+    /// Coordinates of the nodes: the inputs first, then the hidden nodes, then the outputs
+    #[getter(coordinates)]
+    fn py_coordinates(&self) -> Vec<Vec<f64>> {
+        self.coordinates.clone()
+    }
+
+    /// Links as (source, target, weight), with the indices of the nodes
+    #[getter(links)]
+    fn py_links(&self) -> Vec<(usize, usize, f64)> {
+        self.get_links()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "SubstrateNetwork(inputs={}, hidden={}, outputs={}, links={})",
+            self.n_inputs,
+            self.get_n_hidden(),
+            self.n_outputs,
+            self.get_n_links()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// CPPN returning the x coordinate of the target, with an identity output
+    fn get_cppn(outputs: u32) -> Genome {
+        let mut cppn = Genome::new(0, 4, outputs);
+        for gene in &mut cppn.genes {
+            gene.weight = if gene.from == 3 { 1.0 } else { 0.0 };
+        }
+        for output in 5..5 + outputs {
+            cppn.set_activation(output, Activation::Identity).unwrap();
+        }
+        cppn
+    }
+
+    #[test]
+    fn substrate() {
+        let inputs = vec![vec![-1.0, -1.0], vec![1.0, -1.0]];
+        let hidden = vec![vec![vec![-0.75, 0.0], vec![0.1, 0.0], vec![1.0, 0.0]]];
+        let outputs = vec![vec![0.0, 1.0]];
+        let mut substrate = Substrate::new(inputs.clone(), hidden, outputs.clone()).unwrap();
+        substrate.activation = Activation::Identity;
+        substrate.weight_threshold = 0.5;
+        assert_eq!(substrate.get_cppn_inputs(), 4);
+
+        let mut network = substrate.build_network(&mut get_cppn(1)).unwrap();
+        assert_eq!(network.get_n_hidden(), 3);
+        // the hidden node at x = 0.1 and the output at x = 0 are under the threshold
+        let mut links = network.get_links();
+        links.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)).then(a.2.total_cmp(&b.2)));
+        assert_eq!(
+            links,
+            vec![(0, 2, -1.5), (0, 4, 3.0), (1, 2, -1.5), (1, 4, 3.0)]
+        );
+        assert_eq!(network.compute(&[1.0, 2.0]).unwrap(), vec![0.0]);
+        assert!(network.compute(&[1.0]).is_err());
+
+        // a single layer links the inputs to the outputs
+        let substrate = Substrate::new(inputs, Vec::new(), vec![vec![1.0, 1.0]]).unwrap();
+        let mut network = substrate.build_network(&mut get_cppn(2)).unwrap();
+        assert_eq!(network.get_n_links(), 2);
+        // the bias is queried from the origin, giving the same weight as the links
+        let expected = Activation::Sigmoid.apply(3.0 * 1.0 + 3.0 * 0.5 + 3.0);
+        assert_eq!(network.compute(&[1.0, 0.5]).unwrap(), vec![expected]);
+
+        assert!(substrate.build_network(&mut Genome::new(0, 2, 1)).is_err());
+        assert!(Substrate::new(vec![vec![0.0]], Vec::new(), vec![vec![0.0, 1.0]]).is_err());
+        assert!(Substrate::new(Vec::new(), Vec::new(), vec![vec![0.0]]).is_err());
+    }
+
+    #[test]
+    fn computation_order() {
+        // 0 -> 2 -> 1 -> 3, node 2 being computed before node 1, and a loop on the output
+        let mut network = SubstrateNetwork::new(
+            vec![vec![0.0]; 4],
+            1,
+            1,
+            vec![0.0; 3],
+            vec![(2, 1, 1.0), (0, 2, 2.0), (1, 3, 1.0), (3, 3, 1.0)],
+            Activation::Identity,
+        )
+        .unwrap();
+        assert_eq!(network.compute(&[1.0]).unwrap(), vec![2.0]);
+        assert_eq!(network.compute(&[1.0]).unwrap(), vec![4.0]);
+        network.reset();
+        assert_eq!(network.compute(&[1.0]).unwrap(), vec![2.0]);
+    }
+
+    #[test]
+    fn invalid_network() {
+        let new = |n_outputs, bias: usize, links| {
+            SubstrateNetwork::new(
+                vec![vec![0.0]; 3],
+                1,
+                n_outputs,
+                vec![0.0; bias],
+                links,
+                Activation::Identity,
+            )
+        };
+        assert!(new(1, 2, vec![(0, 2, 1.0)]).is_ok());
+        assert!(matches!(
+            new(3, 2, Vec::new()),
+            Err(NeatError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            new(1, 3, Vec::new()),
+            Err(NeatError::InvalidConfig(_))
+        ));
+        // links from a node that doesn't exist, and to an input
+        assert!(matches!(
+            new(1, 2, vec![(3, 2, 1.0)]),
+            Err(NeatError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            new(1, 2, vec![(1, 0, 1.0)]),
+            Err(NeatError::InvalidConfig(_))
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+use super::{Genome, Species};
+#[cfg(feature = "python")]
+use pyo3::*;
+
+/// Formats an option the way python would print it
+#[cfg(feature = "python")]
+fn py_option<T: std::fmt::Display>(value: Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => String::from("None"),
+    }
+}
+
+/// Read-only view of a genome of the population
+#[cfg_attr(feature = "python", pyclass(module = "rust_neat"))]
+#[derive(Clone)]
+pub struct GenomeInfo {
+    /// Id of the genome
+    pub id: u32,
+    /// Fitness given by the last evaluation
+    pub fitness: f64,
+    /// Fitness shared with the other genomes of its species
+    pub adj_fitness: f64,
+    /// Number of real-time ticks the genome lived through
+    pub age: u32,
+    /// Id of the species of the genome, None if it wasn't assigned one
+    pub species_id: Option<u32>,
+}
+
+impl GenomeInfo {
+    /// Returns the view of a genome, `species_id` being the id of its species
+    pub fn new(genome: &Genome, species_id: Option<u32>) -> Self {
+        GenomeInfo {
+            id: genome.id,
+            fitness: genome.fitness,
+            adj_fitness: genome.adj_fitness,
+            age: genome.age,
+            species_id,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl GenomeInfo {
+    #[getter(id)]
+    fn py_id(&self) -> u32 {
+        self.id
+    }
+
+    #[getter(fitness)]
+    fn py_fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    #[getter(adj_fitness)]
+    fn py_adj_fitness(&self) -> f64 {
+        self.adj_fitness
+    }
+
+    /// Number of real-time ticks the genome lived through
+    #[getter(age)]
+    fn py_age(&self) -> u32 {
+        self.age
+    }
+
+    /// Id of the species of the genome, None if it wasn't assigned one
+    #[getter(species_id)]
+    fn py_species_id(&self) -> Option<u32> {
+        self.species_id
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "GenomeInfo(id={}, fitness={}, adj_fitness={}, age={}, species_id={})",
+            self.id,
+            self.fitness,
+            self.adj_fitness,
+            self.age,
+            py_option(self.species_id)
+        )
+    }
+}
+
+/// Returns the views of the genomes of a population
+#[cfg(feature = "python")]
+pub fn get_population_info(population: &[Genome], species: &[Species]) -> Vec<GenomeInfo> {
+    population
+        .iter()
+        .zip(Species::get_species_ids(species, population.len()))
+        .map(|(genome, species_id)| GenomeInfo::new(genome, species_id))
+        .collect()
+}
+
+/// Read-only view of a species
+#[cfg_attr(feature = "python", pyclass(module = "rust_neat"))]
+#[derive(Clone)]
+pub struct SpeciesInfo {
+    /// Id of the species
+    pub id: u32,
+    /// Number of genomes in the species
+    pub size: usize,
+    /// Genome the others are compared to when they are assigned a species
+    pub representative: Genome,
+    /// Best fitness ever reached in the species, None if it was never evaluated
+    pub best_fitness: Option<f64>,
+    /// Number of generations the species went through
+    pub age: u32,
+    /// Number of generations since the best fitness of the species last improved
+    pub stagnation: u32,
+}
+
+impl SpeciesInfo {
+    /// Returns the view of a species
+    pub fn new(species: &Species) -> Self {
+        SpeciesInfo {
+            id: species.get_id(),
+            size: species.population.len(),
+            representative: species.rep_genome.clone(),
+            best_fitness: species.get_best_fitness(),
+            age: species.get_age(),
+            stagnation: species.get_stagnation(),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SpeciesInfo {
+    #[getter(id)]
+    fn py_id(&self) -> u32 {
+        self.id
+    }
+
+    /// Number of genomes in the species
+    #[getter(size)]
+    fn py_size(&self) -> usize {
+        self.size
+    }
+
+    #[getter(representative)]
+    fn py_representative(&self) -> Genome {
+        self.representative.clone()
+    }
+
+    /// Best fitness ever reached in the species, None if it was never evaluated
+    #[getter(best_fitness)]
+    fn py_best_fitness(&self) -> Option<f64> {
+        self.best_fitness
+    }
+
+    /// Number of generations the species went through
+    #[getter(age)]
+    fn py_age(&self) -> u32 {
+        self.age
+    }
+
+    /// Number of generations since the best fitness of the species last improved
+    #[getter(stagnation)]
+    fn py_stagnation(&self) -> u32 {
+        self.stagnation
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "SpeciesInfo(id={}, size={}, best_fitness={}, age={}, stagnation={})",
+            self.id,
+            self.size,
+            py_option(self.best_fitness),
+            self.age,
+            self.stagnation
+        )
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+
+/**
+Keeps track of the structural mutations that happened during the run.
+The same mutation always gets the same historical marking (and the same node id when splitting a gene),
+which is what allows genomes to be lined up when computing differences and doing crossovers.
+*/
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InnovationHistory {
+    /// Historical marking of every link created, by (from, to)
+    #[cfg_attr(feature = "serde", serde(with = "links_serde"))]
+    links: HashMap<(u32, u32), u32>,
+    /// Id of the node created when splitting a gene, by historical marking of the split gene
+    splits: BTreeMap<u32, u32>,
+    next_hm: u32,
+    next_node_id: u32,
+}
+
+impl InnovationHistory {
+    /// Creates the history matching the genes created by `Genome::new`
+    pub fn new(n_inputs: u32, n_outputs: u32) -> Self {
+        let mut history = InnovationHistory {
+            links: HashMap::new(),
+            splits: BTreeMap::new(),
+            next_hm: 0,
+            next_node_id: n_inputs + n_outputs + 1, // inputs + ouputs + bias
+        };
+        for i in 1..=n_inputs {
+            for j in n_inputs + 1..=n_inputs + n_outputs {
+                history.get_link_hm(i, j);
+            }
+        }
+        history
+    }
+
+    /// Returns the historical marking of the link between `from` and `to`, creating one if needed
+    pub fn get_link_hm(&mut self, from: u32, to: u32) -> u32 {
+        let next_hm = &mut self.next_hm;
+        *self.links.entry((from, to)).or_insert_with(|| {
+            *next_hm += 1;
+            *next_hm - 1
+        })
+    }
+
+    /// Returns the id of the node created by splitting the gene `hm`, creating one if needed
+    pub fn get_split_node(&mut self, hm: u32) -> u32 {
+        let next_node_id = &mut self.next_node_id;
+        *self.splits.entry(hm).or_insert_with(|| {
+            *next_node_id += 1;
+            *next_node_id - 1
+        })
+    }
+
+    /// Returns a node id that was never used
+    pub fn get_new_node(&mut self) -> u32 {
+        self.next_node_id += 1;
+        self.next_node_id - 1
+    }
+}
+
+/// JSON maps can only have string keys, the links are saved as a sorted list of (from, to, hm)
+#[cfg(feature = "serde")]
+mod links_serde {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        links: &HashMap<(u32, u32), u32>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut links: Vec<(u32, u32, u32)> = links
+            .iter()
+            .map(|((from, to), hm)| (*from, *to, *hm))
+            .collect();
+        links.sort_unstable();
+        links.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<(u32, u32), u32>, D::Error> {
+        Ok(Vec::<(u32, u32, u32)>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(from, to, hm)| ((from, to), hm))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_mutation_same_marking() {
+        let mut history = InnovationHistory::new(2, 1);
+        // initial genes are 1 -> 3 and 2 -> 3
+        assert_eq!(history.get_link_hm(1, 3), 0);
+        assert_eq!(history.get_link_hm(2, 3), 1);
+        assert_eq!(history.get_link_hm(0, 3), 2);
+        assert_eq!(history.get_link_hm(0, 3), 2);
+
+        assert_eq!(history.get_split_node(1), 4);
+        assert_eq!(history.get_split_node(1), 4);
+        assert_eq!(history.get_new_node(), 5);
+        assert_eq!(history.get_split_node(0), 6);
+    }
+}
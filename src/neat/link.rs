@@ -1,4 +1,3 @@
-
 #[derive(Clone)]
 pub struct LinkFrom {
     pub from: u32,
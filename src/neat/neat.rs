@@ -1,13 +1,41 @@
+#[cfg(feature = "serde")]
+use super::checkpoint::{self, Checkpoint};
+use super::get_settings;
+#[cfg(feature = "python")]
+use super::info::get_population_info;
+use super::pareto;
+#[cfg(feature = "python")]
+use super::reporter::PyReporter;
+use super::rtneat;
+use super::Activation;
+use super::Activations;
 use super::Genome;
+use super::InnovationHistory;
+use super::NeatError;
+use super::NoveltySearch;
+use super::RealTime;
+use super::Reporter;
 use super::Species;
+#[cfg(feature = "serde")]
+use super::{set_settings, Settings};
+use super::{GenerationStats, Statistics};
+#[cfg(feature = "python")]
+use super::{GenomeInfo, SpeciesInfo, StdOutReporter};
+use super::{StopReason, Termination};
+#[cfg(feature = "python")]
 use pyo3::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use std::fmt;
+#[cfg(feature = "python")]
+use std::time::Duration;
+use std::time::Instant;
 
 /**
  * Main class.
  * This class allows you to train an AI.
  */
-#[pyclass]
+#[cfg_attr(feature = "python", pyclass(module = "rust_neat"))]
 pub struct NEAT {
     pop: Vec<Genome>,
     species: Vec<Species>,
@@ -16,6 +44,27 @@ pub struct NEAT {
     genome_next_id: u32,
     species_next_id: u32,
     generation: i32,
+    innovations: InnovationHistory,
+    /// Max difference between two genomes of the same species, adjusted each generation
+    compatibility_threshold: f64,
+    /// Best genome ever evaluated
+    best_genome: Option<Genome>,
+    /// Generation in which `best_genome` was found
+    best_generation: i32,
+    /// Best genome of the last evaluated generation
+    generation_best: Option<Genome>,
+    reporters: Vec<Box<dyn Reporter>>,
+    statistics: Statistics,
+    /// Source of all the randomness of the run
+    rng: ChaCha8Rng,
+    /// Archive and parameters of the novelty search, None when selecting on the fitness
+    novelty: Option<NoveltySearch>,
+    /// Objectives of each genome of the last evaluated generation, empty unless it had several objectives
+    objectives: Vec<Vec<f64>>,
+    /// Real-time evolution, None when the population evolves by generations only
+    real_time: Option<RealTime>,
+    /// Activations of the new nodes, initialized from the settings
+    activations: Activations,
 }
 
 impl fmt::Display for NEAT {
@@ -31,11 +80,29 @@ impl fmt::Display for NEAT {
     }
 }
 
-#[pymethods]
 impl NEAT {
-    #[new]
-    #[args(pop_size, inputs, outputs)]
-    pub fn new(pop_size: usize, inputs: usize, outputs: usize) -> Self {
+    /**
+    Creates the initial population.
+    Runs with the same seed and fitness function are identical, without a seed the run is random.
+    */
+    pub fn new(
+        pop_size: usize,
+        inputs: usize,
+        outputs: usize,
+        seed: Option<u64>,
+    ) -> Result<Self, NeatError> {
+        if pop_size == 0 {
+            return Err(NeatError::InvalidConfig(String::from(
+                "population size must be at least 1",
+            )));
+        }
+        if inputs == 0 || outputs == 0 {
+            return Err(NeatError::InvalidConfig(format!(
+                "networks need at least 1 input and 1 output, got {} inputs and {} outputs",
+                inputs, outputs
+            )));
+        }
+
         let mut neat = NEAT {
             pop: Vec::new(),
             species: Vec::new(),
@@ -44,51 +111,1013 @@ impl NEAT {
             genome_next_id: 0,
             species_next_id: 0,
             generation: 0,
+            innovations: InnovationHistory::new(inputs as u32, outputs as u32),
+            compatibility_threshold: get_settings().max_difference,
+            best_genome: None,
+            best_generation: 0,
+            generation_best: None,
+            reporters: Vec::new(),
+            statistics: Statistics::new(),
+            rng: match seed {
+                Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+                None => ChaCha8Rng::from_entropy(),
+            },
+            novelty: None,
+            objectives: Vec::new(),
+            real_time: None,
+            activations: Activations::from_settings(&get_settings()),
         }
         .populate(pop_size)
         .mutate_initial_pop();
 
         neat.compute_new_pop_species();
 
-        neat
+        Ok(neat)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl NEAT {
+    /**
+    Creates the initial population.
+    Runs with the same seed and fitness function are identical, without a seed the run is random.
+    */
+    #[new]
+    #[args(pop_size, inputs, outputs, seed = "None")]
+    fn py_new(
+        pop_size: usize,
+        inputs: usize,
+        outputs: usize,
+        seed: Option<u64>,
+    ) -> Result<Self, NeatError> {
+        NEAT::new(pop_size, inputs, outputs, seed)
     }
 
     fn __str__(&self) -> String {
         format!("{}", self)
     }
 
-    #[args(fitness_func)]
-    pub fn run_one_gen(&mut self, fitness_function: PyObject) {
+    /**
+    Runs one generation: the population is replaced by the offspring of the previous generation
+    (except on the first call), and then evaluated with `fitness_function`.
+    With novelty search, `fitness_function` returns the behaviour of the genome, or a tuple of its
+    behaviour and fitness. With `objectives`, it returns a list of objectives to maximize.
+    */
+    #[pyo3(name = "run_one_gen")]
+    #[args(fitness_func, objectives = "false")]
+    fn py_run_one_gen(
+        &mut self,
+        fitness_function: PyObject,
+        objectives: bool,
+    ) -> Result<(), NeatError> {
         let gil = Python::acquire_gil();
         let py = gil.python();
-        for i in 0..self.pop.len() {
-            let genome = PyCell::new(py, self.pop.remove(i)).unwrap();
-            let fitness: f64;
-            {
-                let genome_ref = genome.borrow_mut();
-                fitness = fitness_function
-                    .call1(py, (genome_ref,))
-                    .unwrap()
-                    .extract(py)
-                    .unwrap();
+        if objectives {
+            self.check_objectives_mode()?;
+            self.run_one_gen_objectives_with(|genome| {
+                call_objectives_function(py, &fitness_function, genome)
+            })
+        } else if self.novelty.is_some() {
+            self.run_one_gen_novelty_with(|genome| {
+                call_behaviour_function(py, &fitness_function, genome)
+            })
+        } else {
+            self.run_one_gen_with(|genome| call_fitness_function(py, &fitness_function, genome))
+        }
+    }
+
+    /**
+    Runs generations until one of the criteria is met.
+    Returns the best genome found and the reason why the run stopped.
+    `time_limit` is in seconds.
+    With novelty search, `fitness_function` returns the behaviour of the genome, or a tuple of its
+    behaviour and fitness. With `objectives`, it returns a list of objectives to maximize,
+    the first one being used as the fitness.
+    */
+    #[pyo3(name = "run")]
+    #[args(
+        fitness_function,
+        max_generations = "None",
+        fitness_threshold = "None",
+        time_limit = "None",
+        stagnation_limit = "None",
+        objectives = "false"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn py_run(
+        &mut self,
+        fitness_function: PyObject,
+        max_generations: Option<u32>,
+        fitness_threshold: Option<f64>,
+        time_limit: Option<f64>,
+        stagnation_limit: Option<u32>,
+        objectives: bool,
+    ) -> Result<(Genome, String), NeatError> {
+        let time_limit = match time_limit {
+            Some(seconds) if !seconds.is_finite() || seconds < 0.0 => {
+                return Err(NeatError::InvalidConfig(format!(
+                    "time limit must be a positive number of seconds, got {}",
+                    seconds
+                )))
             }
-            let mut genome: Genome = genome.extract().unwrap();
-            genome.fitness = fitness;
-            self.pop.insert(i, genome);
+            Some(seconds) => Some(Duration::from_secs_f64(seconds)),
+            None => None,
+        };
+        let termination = Termination {
+            max_generations,
+            fitness_threshold,
+            time_limit,
+            stagnation_limit,
+        };
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let (best, reason) = if objectives {
+            self.check_objectives_mode()?;
+            self.run_objectives_with(
+                |genome| call_objectives_function(py, &fitness_function, genome),
+                &termination,
+            )?
+        } else if self.novelty.is_some() {
+            self.run_novelty_with(
+                |genome| call_behaviour_function(py, &fitness_function, genome),
+                &termination,
+            )?
+        } else {
+            self.run_with(
+                |genome| call_fitness_function(py, &fitness_function, genome),
+                &termination,
+            )?
+        };
+        Ok((best, reason.to_string()))
+    }
+
+    /**
+    Selects the genomes on the novelty of their behaviour instead of their fitness.
+    The novelty is the mean distance to the `k` nearest behaviours of the population and the archive,
+    and behaviours more novel than `threshold` are archived. `fitness_weight`, between 0 and 1,
+    blends the fitness into the score.
+    */
+    #[pyo3(name = "enable_novelty_search")]
+    #[args(threshold, k = "15", fitness_weight = "0.0")]
+    fn py_enable_novelty_search(
+        &mut self,
+        threshold: f64,
+        k: usize,
+        fitness_weight: f64,
+    ) -> Result<(), NeatError> {
+        self.set_novelty_search(Some(NoveltySearch::new(k, threshold, fitness_weight)?));
+        Ok(())
+    }
+
+    /**
+    Sets the activation of the nodes added by mutations, the activation of the outputs, also given to
+    the current population, and the chance of an offspring getting a random activation, for this run only.
+    The activations are sigmoid, tanh, relu, identity, gaussian, sin and abs.
+    */
+    #[pyo3(name = "set_activations")]
+    #[args(
+        hidden = "\"sigmoid\"",
+        output = "\"sigmoid\"",
+        mutation_chance = "0.0"
+    )]
+    fn py_set_activations(
+        &mut self,
+        hidden: &str,
+        output: &str,
+        mutation_chance: f64,
+    ) -> Result<(), NeatError> {
+        self.set_activations(hidden.parse()?, output.parse()?, mutation_chance)
+    }
+
+    /// Selects the genomes on their fitness again, the archive is lost
+    #[pyo3(name = "disable_novelty_search")]
+    fn py_disable_novelty_search(&mut self) {
+        self.set_novelty_search(None);
+    }
+
+    /**
+    Evolves the population in real time: the genomes are evaluated while they live, and every
+    `interval` ticks the worst genome at least `min_age` ticks old is replaced by a new offspring.
+    */
+    #[pyo3(name = "enable_real_time")]
+    #[args(interval, min_age = "0")]
+    fn py_enable_real_time(&mut self, interval: u32, min_age: u32) -> Result<(), NeatError> {
+        self.set_real_time(Some(RealTime::new(interval, min_age)?));
+        Ok(())
+    }
+
+    #[pyo3(name = "disable_real_time")]
+    fn py_disable_real_time(&mut self) {
+        self.set_real_time(None);
+    }
+
+    /// Ages the population by one tick, returns the index of the replaced genome if there is one
+    #[pyo3(name = "tick")]
+    fn py_tick(&mut self) -> Result<Option<usize>, NeatError> {
+        self.tick()
+    }
+
+    /// Sets the fitness of the genome at `index` in the population
+    #[pyo3(name = "set_fitness")]
+    fn py_set_fitness(&mut self, index: usize, fitness: f64) -> Result<(), NeatError> {
+        self.set_fitness(index, fitness)
+    }
+
+    /// Computes the outputs of the genome at `index` in the population
+    #[pyo3(name = "compute")]
+    fn py_compute(&mut self, index: usize, inputs: Vec<f64>) -> Result<Vec<f64>, NeatError> {
+        self.get_genome_mut(index)?.compute(inputs)
+    }
+
+    /// Behaviours archived by the novelty search, None when it is disabled
+    #[getter(novelty_archive)]
+    fn py_novelty_archive(&self) -> Option<Vec<Vec<f64>>> {
+        self.novelty
+            .as_ref()
+            .map(|novelty| novelty.get_archive().to_vec())
+    }
+
+    /**
+    Genomes of the last generation that no other genome dominates, with their objectives.
+    Empty unless the generation was run with `objectives`.
+    */
+    #[getter(pareto_front)]
+    fn py_pareto_front(&self) -> Vec<(Genome, Vec<f64>)> {
+        self.get_pareto_front()
+            .into_iter()
+            .map(|(genome, objectives)| (genome.clone(), objectives.to_vec()))
+            .collect()
+    }
+
+    /// Best genome ever evaluated, None before the first generation
+    #[getter(best_genome)]
+    fn py_best_genome(&self) -> Option<Genome> {
+        self.best_genome.clone()
+    }
+
+    /// Generation in which the best genome was found, None before the first generation
+    #[getter(best_generation)]
+    fn py_best_generation(&self) -> Option<i32> {
+        self.get_best_generation()
+    }
+
+    /// Best genome of the last generation, None before the first generation
+    #[getter(generation_best)]
+    fn py_generation_best(&self) -> Option<Genome> {
+        self.generation_best.clone()
+    }
+
+    #[getter]
+    fn generation(&self) -> i32 {
+        self.generation
+    }
+
+    /// Views of the genomes of the current population
+    #[getter(population)]
+    fn py_population(&self) -> Vec<GenomeInfo> {
+        get_population_info(&self.pop, &self.species)
+    }
+
+    /// Views of the current species
+    #[getter(species)]
+    fn py_species(&self) -> Vec<SpeciesInfo> {
+        self.species.iter().map(SpeciesInfo::new).collect()
+    }
+
+    /// Statistics of every generation evaluated so far
+    #[getter(statistics)]
+    fn py_statistics(&self) -> Statistics {
+        self.statistics.clone()
+    }
+
+    /**
+    Registers an object receiving the events of the run.
+    The object can define any of the methods `start_generation(generation)`,
+    `post_evaluate(generation, population, species, best)`, `species_stagnant(generation, species)`,
+    `found_solution(generation, best)` and `end_generation(generation, population, species)`.
+    Use `StdOutReporter()` to print the progress of the run.
+    */
+    #[pyo3(name = "add_reporter")]
+    fn py_add_reporter(&mut self, py: Python, reporter: PyObject) {
+        match reporter.extract::<StdOutReporter>(py) {
+            Ok(reporter) => self.add_reporter(Box::new(reporter)),
+            Err(_) => self.add_reporter(Box::new(PyReporter::new(reporter))),
+        }
+    }
+
+    /**
+    Saves the state of the run in a JSON file, to be resumed with `NEAT.load_checkpoint`.
+    The reporters aren't saved.
+    */
+    #[cfg(feature = "serde")]
+    #[pyo3(name = "save_checkpoint")]
+    fn py_save_checkpoint(&self, path: &str) -> Result<(), NeatError> {
+        self.save_checkpoint(path)
+    }
+
+    /// Restores a run from the checkpoint JSON written when pickling
+    #[cfg(feature = "serde")]
+    #[staticmethod]
+    #[pyo3(name = "_from_state")]
+    fn py_from_state(state: &str) -> Result<Self, NeatError> {
+        Ok(NEAT::from_checkpoint(Checkpoint::from_json(state)?))
+    }
+
+    /**
+    Pickles the run as its checkpoint, restored directly by `_from_state` without building a new
+    population. The reporters aren't kept.
+    */
+    #[cfg(feature = "serde")]
+    fn __reduce__(slf: &PyCell<Self>) -> Result<(PyObject, (String,)), NeatError> {
+        let restore = slf.get_type().getattr("_from_state")?.into();
+        Ok((restore, (slf.borrow().to_checkpoint().to_json()?,)))
+    }
+
+    /**
+    Resumes a run saved with `save_checkpoint`.
+    The settings are replaced by the ones of the checkpoint.
+    */
+    #[cfg(feature = "serde")]
+    #[staticmethod]
+    #[pyo3(name = "load_checkpoint")]
+    fn py_load_checkpoint(path: &str) -> Result<Self, NeatError> {
+        NEAT::load_checkpoint(path)
+    }
+}
+
+/// Calls the python fitness function on a copy of the genome
+#[cfg(feature = "python")]
+fn call_fitness_function(
+    py: Python,
+    fitness_function: &PyObject,
+    genome: &Genome,
+) -> Result<f64, NeatError> {
+    let genome_id = genome.id;
+    let genome = PyCell::new(py, genome.clone())?;
+    let fitness = fitness_function.call1(py, (genome,))?;
+    fitness
+        .extract(py)
+        .map_err(|_| NeatError::NonNumericFitness {
+            genome_id,
+            type_name: fitness
+                .as_ref(py)
+                .get_type()
+                .name()
+                .unwrap_or("unknown")
+                .to_string(),
+        })
+}
+
+/**
+Calls the python behaviour function on a copy of the genome.
+It returns the behaviour, or a tuple of the behaviour and the fitness, which is 0 if not given.
+*/
+#[cfg(feature = "python")]
+fn call_behaviour_function(
+    py: Python,
+    behaviour_function: &PyObject,
+    genome: &Genome,
+) -> Result<(Vec<f64>, f64), NeatError> {
+    let genome = PyCell::new(py, genome.clone())?;
+    let result = behaviour_function.call1(py, (genome,))?;
+    if let Ok(behaviour) = result.extract(py) {
+        return Ok((behaviour, 0.0));
+    }
+    result.extract(py).map_err(|_| {
+        NeatError::from(exceptions::PyTypeError::new_err(format!(
+            "the behaviour function must return a list of numbers or a (list, fitness) tuple, got '{}'",
+            result
+                .as_ref(py)
+                .get_type()
+                .name()
+                .unwrap_or("unknown")
+        )))
+    })
+}
+
+/// Calls the python objectives function on a copy of the genome
+#[cfg(feature = "python")]
+fn call_objectives_function(
+    py: Python,
+    objectives_function: &PyObject,
+    genome: &Genome,
+) -> Result<Vec<f64>, NeatError> {
+    let genome = PyCell::new(py, genome.clone())?;
+    let objectives = objectives_function.call1(py, (genome,))?;
+    objectives.extract(py).map_err(|_| {
+        NeatError::from(exceptions::PyTypeError::new_err(format!(
+            "the objectives function must return a list of numbers, got '{}'",
+            objectives.as_ref(py).get_type().name().unwrap_or("unknown")
+        )))
+    })
+}
+
+#[cfg(feature = "python")]
+impl NEAT {
+    /// The objectives replace the behaviours of the novelty search, they can't be used together
+    fn check_objectives_mode(&self) -> Result<(), NeatError> {
+        if self.novelty.is_some() {
+            return Err(NeatError::InvalidConfig(String::from(
+                "novelty search can't be used with multiple objectives",
+            )));
         }
+        Ok(())
+    }
+}
+
+/// Running
+impl NEAT {
+    /// Same as `run_one_gen`, with a rust fitness function
+    pub fn run_one_gen_with<F>(&mut self, fitness_function: F) -> Result<(), NeatError>
+    where
+        F: FnMut(&mut Genome) -> Result<f64, NeatError>,
+    {
+        self.run_generation(|neat| {
+            neat.evaluate(fitness_function)?;
+            Ok(neat.pop.iter().map(|genome| genome.fitness).collect())
+        })
+    }
+
+    /**
+    Same as `run_one_gen` with novelty search, `behaviour_function` returning the behaviour and the
+    fitness of the genome
+    */
+    pub fn run_one_gen_novelty_with<F>(&mut self, behaviour_function: F) -> Result<(), NeatError>
+    where
+        F: FnMut(&mut Genome) -> Result<(Vec<f64>, f64), NeatError>,
+    {
+        self.run_generation(|neat| neat.evaluate_novelty(behaviour_function))
+    }
+
+    /**
+    Same as `run_one_gen` with several objectives, `objectives_function` returning the objectives of
+    the genome, to maximize. The genomes are selected with non-dominated sorting and crowding distance.
+    */
+    pub fn run_one_gen_objectives_with<F>(
+        &mut self,
+        objectives_function: F,
+    ) -> Result<(), NeatError>
+    where
+        F: FnMut(&mut Genome) -> Result<Vec<f64>, NeatError>,
+    {
+        self.run_generation(|neat| neat.evaluate_objectives(objectives_function))
+    }
+
+    /**
+    Runs one generation, `evaluate` setting the fitness of the population and returning the score
+    each genome is selected on
+    */
+    fn run_generation<E>(&mut self, evaluate: E) -> Result<(), NeatError>
+    where
+        E: FnOnce(&mut Self) -> Result<Vec<f64>, NeatError>,
+    {
+        let generation = self.generation;
+        self.report(|reporter, _| reporter.start_generation(generation))?;
+        if self.generation > 0 {
+            self.report_stagnant_species()?;
+        }
+        // a failed evaluation restores the previous generation, so that it can be run again
+        let previous = GenerationState::save(self);
+        self.objectives.clear();
+        if self.generation > 0 {
+            self.reproduce();
+            self.adjust_compatibility_threshold();
+            self.compute_new_pop_species();
+        }
+        let evaluation_start = Instant::now();
+        let scores = match evaluate(self) {
+            Ok(scores) => scores,
+            Err(error) => {
+                previous.restore(self);
+                return Err(error);
+            }
+        };
+        let evaluation_time = evaluation_start.elapsed();
+        self.prepare_species_next_gen(&scores);
+        self.update_best_genomes();
+        self.statistics.record(GenerationStats::new(
+            generation,
+            &self.pop,
+            &self.species,
+            self.compatibility_threshold,
+            evaluation_time,
+        ));
+        self.report(|reporter, neat| {
+            reporter.post_evaluate(
+                generation,
+                &neat.pop,
+                &neat.species,
+                neat.generation_best.as_ref().unwrap(),
+            )
+        })?;
 
         self.generation += 1;
+        self.report(|reporter, neat| reporter.end_generation(generation, &neat.pop, &neat.species))
+    }
+
+    /// Same as `run`, with a rust fitness function
+    pub fn run_with<F>(
+        &mut self,
+        mut fitness_function: F,
+        termination: &Termination,
+    ) -> Result<(Genome, StopReason), NeatError>
+    where
+        F: FnMut(&mut Genome) -> Result<f64, NeatError>,
+    {
+        self.run_until(termination, |neat| {
+            neat.run_one_gen_with(&mut fitness_function)
+        })
+    }
+
+    /**
+    Same as `run` with novelty search, `behaviour_function` returning the behaviour and the fitness
+    of the genome. The termination criteria use the fitness.
+    */
+    pub fn run_novelty_with<F>(
+        &mut self,
+        mut behaviour_function: F,
+        termination: &Termination,
+    ) -> Result<(Genome, StopReason), NeatError>
+    where
+        F: FnMut(&mut Genome) -> Result<(Vec<f64>, f64), NeatError>,
+    {
+        self.run_until(termination, |neat| {
+            neat.run_one_gen_novelty_with(&mut behaviour_function)
+        })
+    }
+
+    /**
+    Same as `run` with several objectives, `objectives_function` returning the objectives of the genome.
+    The termination criteria use the fitness, which is the first objective.
+    */
+    pub fn run_objectives_with<F>(
+        &mut self,
+        mut objectives_function: F,
+        termination: &Termination,
+    ) -> Result<(Genome, StopReason), NeatError>
+    where
+        F: FnMut(&mut Genome) -> Result<Vec<f64>, NeatError>,
+    {
+        self.run_until(termination, |neat| {
+            neat.run_one_gen_objectives_with(&mut objectives_function)
+        })
+    }
+
+    /// Calls `run_one_gen` until one of the termination criteria is met
+    fn run_until<G>(
+        &mut self,
+        termination: &Termination,
+        mut run_one_gen: G,
+    ) -> Result<(Genome, StopReason), NeatError>
+    where
+        G: FnMut(&mut Self) -> Result<(), NeatError>,
+    {
+        if termination.is_empty() {
+            return Err(NeatError::InvalidConfig(String::from(
+                "the run needs at least one termination criterion",
+            )));
+        }
+        if termination.max_generations == Some(0) {
+            return Err(NeatError::InvalidConfig(String::from(
+                "the max number of generations must be at least 1",
+            )));
+        }
+
+        let start = Instant::now();
+        let mut generations = 0;
+        loop {
+            run_one_gen(self)?;
+            generations += 1;
+
+            let best_fitness = self.best_genome.as_ref().unwrap().fitness;
+            // the last evaluated generation is `generation - 1`
+            let stagnation = (self.generation - 1 - self.best_generation) as u32;
+
+            let reason = if termination
+                .fitness_threshold
+                .is_some_and(|threshold| best_fitness >= threshold)
+            {
+                Some(StopReason::FitnessThreshold)
+            } else if termination
+                .max_generations
+                .is_some_and(|max| generations >= max)
+            {
+                Some(StopReason::MaxGenerations)
+            } else if termination
+                .time_limit
+                .is_some_and(|limit| start.elapsed() >= limit)
+            {
+                Some(StopReason::TimeLimit)
+            } else if termination
+                .stagnation_limit
+                .is_some_and(|limit| stagnation >= limit)
+            {
+                Some(StopReason::Stagnation)
+            } else {
+                None
+            };
+
+            if let Some(reason) = reason {
+                let best = self.best_genome.clone().unwrap();
+                if reason == StopReason::FitnessThreshold {
+                    let generation = self.generation - 1;
+                    self.report(|reporter, _| reporter.found_solution(generation, &best))?;
+                }
+                return Ok((best, reason));
+            }
+        }
+    }
+}
+
+/// Reporters
+impl NEAT {
+    /// Adds a reporter, called on each event of the run
+    pub fn add_reporter(&mut self, reporter: Box<dyn Reporter>) {
+        self.reporters.push(reporter);
+    }
+
+    /// Calls `event` on every reporter, stops at the first error
+    fn report<F>(&mut self, mut event: F) -> Result<(), NeatError>
+    where
+        F: FnMut(&mut dyn Reporter, &Self) -> Result<(), NeatError>,
+    {
+        let mut reporters = std::mem::take(&mut self.reporters);
+        let result = reporters
+            .iter_mut()
+            .try_for_each(|reporter| event(reporter.as_mut(), self));
+        self.reporters = reporters;
+        result
+    }
+}
+
+/// Best genomes
+impl NEAT {
+    /// Returns the best genome ever evaluated
+    pub fn get_best_genome(&self) -> Option<&Genome> {
+        self.best_genome.as_ref()
+    }
+
+    /// Returns the generation in which the best genome was found
+    pub fn get_best_generation(&self) -> Option<i32> {
+        self.best_genome.as_ref().map(|_| self.best_generation)
+    }
+
+    /// Returns the best genome of the last evaluated generation
+    pub fn get_generation_best(&self) -> Option<&Genome> {
+        self.generation_best.as_ref()
+    }
+
+    /// Updates the best genomes using the population that was just evaluated
+    fn update_best_genomes(&mut self) {
+        let gen_best = self
+            .pop
+            .iter()
+            .max_by(|a, b| a.fitness.total_cmp(&b.fitness))
+            .unwrap();
+        if self
+            .best_genome
+            .as_ref()
+            .is_none_or(|best| gen_best.fitness > best.fitness)
+        {
+            self.best_genome = Some(gen_best.clone());
+            self.best_generation = self.generation;
+        }
+        self.generation_best = Some(gen_best.clone());
+    }
+}
+
+/// Introspection
+impl NEAT {
+    /// Genomes of the current generation
+    pub fn get_population(&self) -> &[Genome] {
+        &self.pop
     }
 
-    pub fn print_species_info(&self) {
-        println!("N° of Species {}", self.species.len());
+    /// Species of the current generation
+    pub fn get_species(&self) -> &[Species] {
+        &self.species
+    }
+
+    /// Number of generations run
+    pub fn get_generation(&self) -> i32 {
+        self.generation
+    }
+
+    /// Statistics of every generation run
+    pub fn get_statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    /// Returns the species id of each genome of the population, in the same order
+    pub fn get_pop_species_ids(&self) -> Vec<Option<u32>> {
+        Species::get_species_ids(&self.species, self.pop.len())
+    }
+}
+
+/// Multiple objectives
+impl NEAT {
+    /**
+    Returns the genomes of the last generation that no other genome dominates, with their objectives.
+    Empty unless the generation was run with several objectives.
+    */
+    pub fn get_pareto_front(&self) -> Vec<(&Genome, &[f64])> {
+        match pareto::get_fronts(&self.objectives).first() {
+            Some(front) => front
+                .iter()
+                .map(|i| (&self.pop[*i], self.objectives[*i].as_slice()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the objectives of each genome of the last generation, empty unless it had several
+    pub fn get_objectives(&self) -> &[Vec<f64>] {
+        &self.objectives
+    }
+}
+
+/// Novelty search
+impl NEAT {
+    /**
+    Enables the novelty search, the genomes being selected on the novelty of their behaviour,
+    or disables it with None.
+    The runs then need a behaviour function, with `run_novelty_with` or `run_one_gen_novelty_with`.
+    */
+    pub fn set_novelty_search(&mut self, novelty: Option<NoveltySearch>) {
+        self.novelty = novelty;
+    }
+
+    /// Novelty search used by the run, None if it evolves on the fitness
+    pub fn get_novelty_search(&self) -> Option<&NoveltySearch> {
+        self.novelty.as_ref()
+    }
+}
+
+/// Real-time evolution
+impl NEAT {
+    /**
+    Enables the real-time evolution, or disables it with None.
+    The genomes are then evaluated while they live, with `set_fitness`, and `tick` replaces the worst of
+    them by new offspring instead of running generations.
+    */
+    pub fn set_real_time(&mut self, real_time: Option<RealTime>) {
+        self.real_time = real_time;
+    }
+
+    /// Real-time state of the run, None if it evolves by generations only
+    pub fn get_real_time(&self) -> Option<&RealTime> {
+        self.real_time.as_ref()
+    }
+
+    /// Returns the genome at this index in the population
+    pub fn get_genome_mut(&mut self, index: usize) -> Result<&mut Genome, NeatError> {
+        let len = self.pop.len();
+        self.pop
+            .get_mut(index)
+            .ok_or(NeatError::IndexOutOfRange { index, len })
+    }
+
+    /// Sets the fitness of the genome at `index` in the population
+    pub fn set_fitness(&mut self, index: usize, fitness: f64) -> Result<(), NeatError> {
+        let genome = self.get_genome_mut(index)?;
+        check_finite(genome.id, fitness)?;
+        genome.fitness = fitness;
+        Ok(())
+    }
+
+    /**
+    Ages the population by one tick. Every `interval` ticks, the worst genome old enough is replaced
+    by a new offspring, and its index in the population is returned.
+    */
+    pub fn tick(&mut self) -> Result<Option<usize>, NeatError> {
+        let Some(real_time) = &mut self.real_time else {
+            return Err(NeatError::InvalidConfig(String::from(
+                "real-time evolution is not enabled",
+            )));
+        };
+        let replace = real_time.tick();
+        let min_age = real_time.get_min_age();
+        for genome in &mut self.pop {
+            genome.age += 1;
+        }
+        if !replace {
+            return Ok(None);
+        }
+        Ok(self.replace_worst(min_age))
+    }
+
+    /**
+    Replaces the genome with the worst adjusted fitness among the ones at least `min_age` ticks old
+    by an offspring of a species chosen on its average fitness.
+    Only the offspring is speciated, the other genomes keep their species.
+    */
+    fn replace_worst(&mut self, min_age: u32) -> Option<usize> {
         for species in &self.species {
-            println!(
-                "Species id: {}, population: {}",
-                species.get_id(),
-                species.population.len()
-            );
+            for individual in &species.population {
+                let genome = &mut self.pop[*individual as usize];
+                genome.adj_fitness = genome.fitness / species.population.len() as f64;
+            }
+        }
+        self.update_best_genomes();
+
+        let worst = (0..self.pop.len())
+            .filter(|i| self.pop[*i].age >= min_age)
+            .min_by(|a, b| {
+                self.pop[*a]
+                    .adj_fitness
+                    .total_cmp(&self.pop[*b].adj_fitness)
+            })?;
+        for species in &mut self.species {
+            species
+                .population
+                .retain(|individual| *individual as usize != worst);
+        }
+        self.species
+            .retain(|species| !species.population.is_empty());
+
+        let id = self.get_next_genome_id();
+        let mut child = if self.species.is_empty() {
+            // the population has a single genome, which is its own parent
+            make_offspring(
+                id,
+                &[&self.pop[worst]],
+                &self.activations,
+                &mut self.rng,
+                &mut self.innovations,
+            )
+        } else {
+            let average_fitness: Vec<f64> = self
+                .species
+                .iter()
+                .map(|species| {
+                    species
+                        .population
+                        .iter()
+                        .map(|individual| self.pop[*individual as usize].fitness)
+                        .sum::<f64>()
+                        / species.population.len() as f64
+                })
+                .collect();
+            let chosen = rtneat::choose_species(&average_fitness, &mut self.rng);
+            let parents = get_parents(&self.pop, &self.species[chosen]);
+            make_offspring(
+                id,
+                &parents,
+                &self.activations,
+                &mut self.rng,
+                &mut self.innovations,
+            )
+        };
+        child.age = 0;
+        child.fitness = 0.0;
+        child.adj_fitness = 0.0;
+        self.pop[worst] = child;
+
+        self.adjust_compatibility_threshold();
+        if let Some(index) = self.get_genome_species(&self.pop[worst]) {
+            self.species[index].population.push(worst as u32);
+        } else {
+            let species_id = self.get_next_species_id();
+            let mut species = Species::new(&self.pop[worst], species_id);
+            species.population.push(worst as u32);
+            self.species.push(species);
         }
+        if let Some(real_time) = &mut self.real_time {
+            real_time.add_replacement();
+        }
+        Some(worst)
+    }
+}
+
+/// Activations
+impl NEAT {
+    /// Activations given to the nodes of the genomes of this run
+    pub fn get_activations(&self) -> Activations {
+        self.activations
+    }
+
+    /**
+    Sets the activation of the nodes added by mutations, the activation of the outputs, also given to
+    the current population, and the chance of an offspring getting a random activation.
+    Only this run uses them, the settings are left unchanged.
+    CPPNs evolved for HyperNEAT usually need other activations than the sigmoid.
+    */
+    pub fn set_activations(
+        &mut self,
+        hidden: Activation,
+        output: Activation,
+        mutation_chance: f64,
+    ) -> Result<(), NeatError> {
+        if !(0.0..=1.0).contains(&mutation_chance) {
+            return Err(NeatError::InvalidConfig(format!(
+                "the activation mutation chance must be between 0 and 1, got {}",
+                mutation_chance
+            )));
+        }
+        self.activations = Activations {
+            hidden,
+            output,
+            mutation_chance,
+        };
+        let outputs = self.n_inputs as u32 + 1..=(self.n_inputs + self.n_outputs) as u32;
+        let genomes = self.pop.iter_mut().chain(
+            self.species
+                .iter_mut()
+                .map(|species| &mut species.rep_genome),
+        );
+        for genome in genomes {
+            for id in outputs.clone() {
+                genome.set_activation(id, output)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checkpoints
+#[cfg(feature = "serde")]
+impl NEAT {
+    /// Returns a copy of the state of the run, along with the current settings and its activations
+    pub fn to_checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            version: checkpoint::VERSION,
+            settings: Settings {
+                hidden_activation: self.activations.hidden,
+                output_activation: self.activations.output,
+                activation_mut_chance: self.activations.mutation_chance,
+                ..get_settings()
+            },
+            n_inputs: self.n_inputs,
+            n_outputs: self.n_outputs,
+            population: self.pop.clone(),
+            species: self.species.clone(),
+            genome_next_id: self.genome_next_id,
+            species_next_id: self.species_next_id,
+            generation: self.generation,
+            innovations: self.innovations.clone(),
+            compatibility_threshold: self.compatibility_threshold,
+            best_genome: self.best_genome.clone(),
+            best_generation: self.best_generation,
+            generation_best: self.generation_best.clone(),
+            statistics: self.statistics.clone(),
+            rng: self.rng.clone(),
+            novelty: self.novelty.clone(),
+            objectives: self.objectives.clone(),
+            real_time: self.real_time.clone(),
+        }
+    }
+
+    /**
+    Resumes a run from a checkpoint. The run gets the activations of the checkpoint, its other settings
+    replace the current ones, which are shared by every run.
+    */
+    pub fn from_checkpoint(checkpoint: Checkpoint) -> Self {
+        let activations = Activations::from_settings(&checkpoint.settings);
+        // the activations of the other runs are initialized from the settings, they stay the same
+        let current = get_settings();
+        set_settings(Settings {
+            hidden_activation: current.hidden_activation,
+            output_activation: current.output_activation,
+            activation_mut_chance: current.activation_mut_chance,
+            ..checkpoint.settings
+        });
+        NEAT {
+            pop: checkpoint.population,
+            species: checkpoint.species,
+            n_inputs: checkpoint.n_inputs,
+            n_outputs: checkpoint.n_outputs,
+            genome_next_id: checkpoint.genome_next_id,
+            species_next_id: checkpoint.species_next_id,
+            generation: checkpoint.generation,
+            innovations: checkpoint.innovations,
+            compatibility_threshold: checkpoint.compatibility_threshold,
+            best_genome: checkpoint.best_genome,
+            best_generation: checkpoint.best_generation,
+            generation_best: checkpoint.generation_best,
+            reporters: Vec::new(),
+            statistics: checkpoint.statistics,
+            rng: checkpoint.rng,
+            novelty: checkpoint.novelty,
+            objectives: checkpoint.objectives,
+            real_time: checkpoint.real_time,
+            activations,
+        }
+    }
+
+    /**
+    Saves the state of the run in a JSON file, to be resumed with `NEAT::load_checkpoint`.
+    The reporters aren't saved.
+    */
+    pub fn save_checkpoint(&self, path: &str) -> Result<(), NeatError> {
+        Ok(std::fs::write(path, self.to_checkpoint().to_json()?)?)
+    }
+
+    /// Resumes a run saved with `save_checkpoint`, the settings are replaced by the ones of the checkpoint
+    pub fn load_checkpoint(path: &str) -> Result<Self, NeatError> {
+        let checkpoint = Checkpoint::from_json(&std::fs::read_to_string(path)?)?;
+        Ok(NEAT::from_checkpoint(checkpoint))
     }
 }
 
@@ -120,21 +1149,146 @@ impl NEAT {
     /// This function is only used to mutate the initial population, don't user otherwise
     fn mutate_initial_pop(mut self) -> Self {
         for genome in &mut self.pop {
-            genome.mutate_weights();
+            genome.mutate_weights(&mut self.rng);
         }
         self
     }
 }
 
-/// Speciation
+/// Evaluation
 impl NEAT {
-    fn get_genome_species(&self, genome: &Genome) -> Option<usize> {
-        for i in 0..self.species.len() {
-            if self.species[i].belongs(genome) {
-                return Some(i);
+    /**
+    Sets the fitness of every genome in the population using `fitness_function`.
+    Stops at the first error, a NaN or infinite fitness being considered as one.
+    */
+    pub fn evaluate<F>(&mut self, mut fitness_function: F) -> Result<(), NeatError>
+    where
+        F: FnMut(&mut Genome) -> Result<f64, NeatError>,
+    {
+        for genome in &mut self.pop {
+            let fitness = fitness_function(genome)?;
+            check_finite(genome.id, fitness)?;
+            genome.fitness = fitness;
+        }
+        Ok(())
+    }
+
+    /**
+    Sets the fitness of every genome in the population using `behaviour_function`, and returns
+    their novelty scores. Every behaviour must have the same length, and the behaviours and the fitness
+    must be finite.
+    */
+    fn evaluate_novelty<F>(&mut self, mut behaviour_function: F) -> Result<Vec<f64>, NeatError>
+    where
+        F: FnMut(&mut Genome) -> Result<(Vec<f64>, f64), NeatError>,
+    {
+        let Some(novelty) = &self.novelty else {
+            return Err(NeatError::InvalidConfig(String::from(
+                "novelty search is not enabled",
+            )));
+        };
+        let mut expected = novelty.get_behaviour_length();
+        let mut behaviours = Vec::with_capacity(self.pop.len());
+        for genome in &mut self.pop {
+            let (behaviour, fitness) = behaviour_function(genome)?;
+            check_finite(genome.id, fitness)?;
+            for value in &behaviour {
+                check_finite(genome.id, *value)?;
+            }
+            let expected = *expected.get_or_insert(behaviour.len());
+            if behaviour.len() != expected {
+                return Err(NeatError::WrongVectorLength {
+                    genome_id: genome.id,
+                    expected,
+                    got: behaviour.len(),
+                });
+            }
+            genome.fitness = fitness;
+            behaviours.push(behaviour);
+        }
+        let fitness: Vec<f64> = self.pop.iter().map(|genome| genome.fitness).collect();
+        Ok(self
+            .novelty
+            .as_mut()
+            .unwrap()
+            .evaluate(behaviours, &fitness))
+    }
+}
+
+/// Evaluation with multiple objectives
+impl NEAT {
+    /**
+    Sets the objectives of every genome in the population using `objectives_function`, the fitness
+    being the first objective, and returns the scores the genomes are selected on.
+    Every genome must have the same number of objectives, all of them finite.
+    */
+    fn evaluate_objectives<F>(&mut self, mut objectives_function: F) -> Result<Vec<f64>, NeatError>
+    where
+        F: FnMut(&mut Genome) -> Result<Vec<f64>, NeatError>,
+    {
+        let mut objectives: Vec<Vec<f64>> = Vec::with_capacity(self.pop.len());
+        for genome in &mut self.pop {
+            let genome_objectives = objectives_function(genome)?;
+            for value in &genome_objectives {
+                check_finite(genome.id, *value)?;
             }
+            // the first genome sets the number of objectives, which can't be 0
+            let expected = objectives
+                .first()
+                .map_or(genome_objectives.len().max(1), Vec::len);
+            if genome_objectives.len() != expected {
+                return Err(NeatError::WrongVectorLength {
+                    genome_id: genome.id,
+                    expected,
+                    got: genome_objectives.len(),
+                });
+            }
+            genome.fitness = genome_objectives[0];
+            objectives.push(genome_objectives);
         }
-        None
+        let scores = self.get_objectives_scores(&objectives);
+        self.objectives = objectives;
+        Ok(scores)
+    }
+
+    /**
+    Returns the score of each genome from its non-dominated front, so that a better front always
+    wins, and from its crowding distance among the genomes of its species in the same front,
+    mapped to `[0, 0.5]`
+    */
+    fn get_objectives_scores(&self, objectives: &[Vec<f64>]) -> Vec<f64> {
+        let fronts = pareto::get_fronts(objectives);
+        let mut scores = vec![0.0; objectives.len()];
+        let species_ids = self.get_pop_species_ids();
+        for (rank, front) in fronts.iter().enumerate() {
+            let mut by_species: Vec<(Option<u32>, Vec<usize>)> = Vec::new();
+            for i in front {
+                match by_species
+                    .iter_mut()
+                    .find(|(species_id, _)| *species_id == species_ids[*i])
+                {
+                    Some((_, members)) => members.push(*i),
+                    None => by_species.push((species_ids[*i], vec![*i])),
+                }
+            }
+            for (_, members) in &by_species {
+                let distances = pareto::get_crowding_distances(objectives, members);
+                for (i, distance) in members.iter().zip(distances) {
+                    scores[*i] =
+                        (fronts.len() - rank) as f64 + distance.atan() / std::f64::consts::PI;
+                }
+            }
+        }
+        scores
+    }
+}
+
+/// Speciation
+impl NEAT {
+    fn get_genome_species(&self, genome: &Genome) -> Option<usize> {
+        self.species
+            .iter()
+            .position(|species| species.belongs(genome, self.compatibility_threshold))
     }
 
     /**
@@ -149,37 +1303,878 @@ impl NEAT {
             if let Some(index) = self.get_genome_species(&self.pop[i]) {
                 self.species[index].population.push(i as u32);
             } else {
-                let species_id = self.get_next_genome_id();
+                let species_id = self.get_next_species_id();
                 let mut species = Species::new(&self.pop[i], species_id);
                 species.population.push(i as u32);
                 self.species.push(species);
             }
         }
+        self.species
+            .retain(|species| !species.population.is_empty());
+    }
+
+    /**
+    Reports the species whose fitness just reached the stagnation limit without improving.
+    The species are kept, the reporters are only told about them.
+    */
+    fn report_stagnant_species(&mut self) -> Result<(), NeatError> {
+        let limit = get_settings().species_stagnation_limit;
+        if limit == 0 {
+            return Ok(());
+        }
+        let stagnant: Vec<Species> = self
+            .species
+            .iter()
+            .filter(|species| species.get_stagnation() == limit)
+            .cloned()
+            .collect();
+        let generation = self.generation;
+        for species in &stagnant {
+            self.report(|reporter, _| reporter.species_stagnant(generation, species))?;
+        }
+        Ok(())
+    }
+
+    /// Moves the compatibility threshold so that the number of species gets closer to the target
+    fn adjust_compatibility_threshold(&mut self) {
+        let settings = get_settings();
+        let step = settings.max_difference_step;
+        if self.species.len() > settings.target_species {
+            self.compatibility_threshold += step;
+        } else if self.species.len() < settings.target_species {
+            self.compatibility_threshold = f64::max(
+                self.compatibility_threshold - step,
+                settings.max_difference_min,
+            );
+        }
     }
 
     /**
     Prepares the species for the next generation.
-    This will set the adjusted fitness & the new representative genomes
+    This will set the adjusted fitness & the new representative genomes.
+    `scores` are what the genomes are selected on: their fitness, or their novelty.
     */
-    fn prepare_species_next_gen(&mut self) {
+    fn prepare_species_next_gen(&mut self, scores: &[f64]) {
         for species in &mut self.species {
-            if species.population.len() == 0 {
+            if species.population.is_empty() {
                 continue;
             }
 
             let mut best_index: u32 = species.population[0];
-            let mut best_fitness: f64 = self.pop[species.population[0] as usize].fitness;
+            let mut best_fitness: f64 = scores[species.population[0] as usize];
             for individual in &species.population {
-                if best_fitness < self.pop[*individual as usize].fitness {
+                if best_fitness < scores[*individual as usize] {
                     best_index = *individual;
-                    best_fitness = self.pop[*individual as usize].fitness;
+                    best_fitness = scores[*individual as usize];
                 }
                 self.pop[*individual as usize].adj_fitness =
-                    self.pop[*individual as usize].fitness / (species.population.len() as f64);
+                    scores[*individual as usize] / (species.population.len() as f64);
                 species.total_shared_fitness += self.pop[*individual as usize].adj_fitness;
             }
 
             species.set_new_rep_genome(&self.pop[best_index as usize]);
+            species.end_generation(best_fitness);
+        }
+    }
+}
+
+/// Reproduction
+impl NEAT {
+    /**
+    Returns the number of offspring of each species.
+    Each species gets a part of the population proportional to its total shared fitness.
+    */
+    fn get_offspring_counts(&self) -> Vec<usize> {
+        let pop_size = self.pop.len();
+        let mut shares: Vec<f64> = self
+            .species
+            .iter()
+            .map(|species| {
+                if species.population.is_empty() {
+                    0.0
+                } else {
+                    f64::max(species.total_shared_fitness, 0.0)
+                }
+            })
+            .collect();
+        let total: f64 = shares.iter().sum();
+        // a total too large for a f64 would give every species a share of 0
+        if total > 0.0 && total.is_finite() {
+            for share in &mut shares {
+                *share *= pop_size as f64 / total;
+            }
+        } else {
+            // no fitness to go by, every species gets the same part
+            let n_alive = self
+                .species
+                .iter()
+                .filter(|species| !species.population.is_empty())
+                .count();
+            for (share, species) in shares.iter_mut().zip(&self.species) {
+                if !species.population.is_empty() {
+                    *share = pop_size as f64 / n_alive as f64;
+                }
+            }
+        }
+
+        let mut counts: Vec<usize> = shares.iter().map(|share| share.floor() as usize).collect();
+        let mut remaining = pop_size.saturating_sub(counts.iter().sum());
+        // the offspring left go to the species with the largest fractional parts
+        let mut order: Vec<usize> = (0..shares.len()).collect();
+        order.sort_by(|a, b| {
+            let a = shares[*a] - shares[*a].floor();
+            let b = shares[*b] - shares[*b].floor();
+            b.total_cmp(&a)
+        });
+        let mut i = 0;
+        while remaining > 0 {
+            let index = order[i % order.len()];
+            if shares[index] > 0.0 {
+                counts[index] += 1;
+                remaining -= 1;
+            }
+            i += 1;
+        }
+        counts
+    }
+
+    /**
+    Replaces the population with the offspring of the best genomes of each species.
+    The parents are compared on their adjusted fitness, which follows the score they were selected on.
+    */
+    fn reproduce(&mut self) {
+        let counts = self.get_offspring_counts();
+        let mut new_pop: Vec<Genome> = Vec::with_capacity(self.pop.len());
+
+        for (species, count) in self.species.iter().zip(counts) {
+            if count == 0 {
+                continue;
+            }
+            let parents = get_parents(&self.pop, species);
+            let mut count = count;
+            if parents.len() > get_settings().elitism_min_species_size {
+                new_pop.push(parents[0].clone());
+                count -= 1;
+            }
+
+            for _ in 0..count {
+                self.genome_next_id += 1;
+                let id = self.genome_next_id - 1;
+                new_pop.push(make_offspring(
+                    id,
+                    &parents,
+                    &self.activations,
+                    &mut self.rng,
+                    &mut self.innovations,
+                ));
+            }
+        }
+
+        self.pop = new_pop;
+    }
+}
+
+/// State changed by the reproduction and the evaluation of a generation
+struct GenerationState {
+    pop: Vec<Genome>,
+    species: Vec<Species>,
+    genome_next_id: u32,
+    species_next_id: u32,
+    innovations: InnovationHistory,
+    compatibility_threshold: f64,
+    rng: ChaCha8Rng,
+    novelty: Option<NoveltySearch>,
+    objectives: Vec<Vec<f64>>,
+}
+
+impl GenerationState {
+    fn save(neat: &NEAT) -> Self {
+        GenerationState {
+            pop: neat.pop.clone(),
+            species: neat.species.clone(),
+            genome_next_id: neat.genome_next_id,
+            species_next_id: neat.species_next_id,
+            innovations: neat.innovations.clone(),
+            compatibility_threshold: neat.compatibility_threshold,
+            rng: neat.rng.clone(),
+            novelty: neat.novelty.clone(),
+            objectives: neat.objectives.clone(),
+        }
+    }
+
+    fn restore(self, neat: &mut NEAT) {
+        neat.pop = self.pop;
+        neat.species = self.species;
+        neat.genome_next_id = self.genome_next_id;
+        neat.species_next_id = self.species_next_id;
+        neat.innovations = self.innovations;
+        neat.compatibility_threshold = self.compatibility_threshold;
+        neat.rng = self.rng;
+        neat.novelty = self.novelty;
+        neat.objectives = self.objectives;
+    }
+}
+
+/// Returns an error unless `value`, computed when evaluating the genome, is a finite number
+fn check_finite(genome_id: u32, value: f64) -> Result<(), NeatError> {
+    if value.is_nan() {
+        Err(NeatError::NanFitness { genome_id })
+    } else if value.is_infinite() {
+        Err(NeatError::InfiniteFitness { genome_id })
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the genomes of the species, sorted by decreasing adjusted fitness
+fn get_parents<'a>(pop: &'a [Genome], species: &Species) -> Vec<&'a Genome> {
+    let mut parents: Vec<&Genome> = species
+        .population
+        .iter()
+        .map(|individual| &pop[*individual as usize])
+        .collect();
+    parents.sort_by(|a, b| b.adj_fitness.total_cmp(&a.adj_fitness));
+    parents
+}
+
+/**
+Returns a mutated offspring of the best parents, the parents being sorted by decreasing adjusted fitness.
+Only the part of the parents given by the survival threshold can reproduce.
+*/
+fn make_offspring(
+    id: u32,
+    parents: &[&Genome],
+    activations: &Activations,
+    rng: &mut ChaCha8Rng,
+    innovations: &mut InnovationHistory,
+) -> Genome {
+    let n_survivors =
+        f64::ceil(parents.len() as f64 * get_settings().survival_threshold).max(1.0) as usize;
+    let mut child = if n_survivors > 1 && rng.gen::<f64>() < get_settings().crossover_chance {
+        let a = parents[rng.gen_range(0..n_survivors)];
+        let b = parents[rng.gen_range(0..n_survivors)];
+        if a.adj_fitness >= b.adj_fitness {
+            Genome::crossover(id, a, b, rng)
+        } else {
+            Genome::crossover(id, b, a, rng)
+        }
+    } else {
+        let mut child = parents[rng.gen_range(0..n_survivors)].clone();
+        child.id = id;
+        child.age = 0;
+        child
+    };
+    child.mutate(activations, innovations, rng);
+    child
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_invalid_config() {
+        assert!(matches!(
+            NEAT::new(0, 2, 1, None),
+            Err(NeatError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            NEAT::new(10, 0, 1, None),
+            Err(NeatError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            NEAT::new(10, 2, 0, None),
+            Err(NeatError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn evaluate_nan_fitness() {
+        let mut neat = NEAT::new(10, 2, 1, None).unwrap();
+        assert!(matches!(
+            neat.evaluate(|_| Ok(f64::NAN)),
+            Err(NeatError::NanFitness { .. })
+        ));
+    }
+
+    #[test]
+    fn evaluate_infinite_fitness() {
+        let mut neat = NEAT::new(10, 2, 1, None).unwrap();
+        assert!(matches!(
+            neat.run_one_gen_with(|_| Ok(f64::INFINITY)),
+            Err(NeatError::InfiniteFitness { .. })
+        ));
+        assert!(matches!(
+            neat.set_fitness(0, f64::NEG_INFINITY),
+            Err(NeatError::InfiniteFitness { .. })
+        ));
+        // the total of the species overflows, the offspring are shared equally
+        for _ in 0..3 {
+            neat.run_one_gen_with(|_| Ok(f64::MAX)).unwrap();
+            assert_eq!(neat.pop.len(), 10);
+        }
+    }
+
+    #[test]
+    fn evaluate_sets_fitness() {
+        let mut neat = NEAT::new(10, 2, 1, None).unwrap();
+        neat.evaluate(|genome| Ok(genome.compute(vec![1.0, 0.0])?[0]))
+            .unwrap();
+        for genome in &neat.pop {
+            assert!(genome.fitness > 0.0 && genome.fitness < 1.0);
+        }
+        assert!(matches!(
+            neat.evaluate(|genome| Ok(genome.compute(vec![1.0])?[0])),
+            Err(NeatError::WrongInputLength {
+                expected: 2,
+                got: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn run_one_gen_keeps_pop_size() {
+        let mut neat = NEAT::new(50, 2, 1, None).unwrap();
+        for _ in 0..5 {
+            neat.run_one_gen_with(|genome| Ok(genome.compute(vec![1.0, 0.0])?[0]))
+                .unwrap();
+            assert_eq!(neat.pop.len(), 50);
+            let n_species_pop: usize = neat
+                .species
+                .iter()
+                .map(|species| species.population.len())
+                .sum();
+            assert_eq!(n_species_pop, 50);
+        }
+        assert_eq!(neat.generation, 5);
+        assert_eq!(neat.get_statistics().get_generations().len(), 5);
+    }
+
+    #[test]
+    fn run_one_gen_failure_keeps_generation() {
+        let mut neat = NEAT::new(20, 2, 1, Some(3)).unwrap();
+        neat.run_one_gen_with(|genome| Ok(genome.id as f64))
+            .unwrap();
+        let get_state = |neat: &NEAT| -> Vec<(u32, f64)> {
+            neat.pop
+                .iter()
+                .map(|genome| (genome.id, genome.fitness))
+                .collect()
+        };
+        let state = get_state(&neat);
+
+        // the callback fails in the middle of the population
+        let mut calls = 0;
+        let result = neat.run_one_gen_with(|_| {
+            calls += 1;
+            if calls == 10 {
+                Err(NeatError::InvalidConfig(String::from("failure")))
+            } else {
+                Ok(1.0)
+            }
+        });
+        assert!(result.is_err());
+        assert_eq!(get_state(&neat), state);
+        assert_eq!(neat.generation, 1);
+        assert_eq!(neat.get_statistics().get_generations().len(), 1);
+
+        // the retry runs as if the failure never happened
+        let mut expected = NEAT::new(20, 2, 1, Some(3)).unwrap();
+        expected
+            .run_one_gen_with(|genome| Ok(genome.id as f64))
+            .unwrap();
+        expected.run_one_gen_with(|_| Ok(1.0)).unwrap();
+        neat.run_one_gen_with(|_| Ok(1.0)).unwrap();
+        assert_eq!(neat.generation, 2);
+        assert_eq!(get_state(&neat), get_state(&expected));
+    }
+
+    #[test]
+    fn run_termination() {
+        let mut neat = NEAT::new(20, 2, 1, None).unwrap();
+        assert!(matches!(
+            neat.run_with(|_| Ok(1.0), &Termination::default()),
+            Err(NeatError::InvalidConfig(_))
+        ));
+        let no_generation = Termination {
+            max_generations: Some(0),
+            ..Termination::default()
+        };
+        assert!(matches!(
+            neat.run_with(|_| Ok(1.0), &no_generation),
+            Err(NeatError::InvalidConfig(_))
+        ));
+        assert_eq!(neat.generation, 0);
+
+        let termination = Termination {
+            max_generations: Some(3),
+            ..Default::default()
+        };
+        let (_, reason) = neat.run_with(|_| Ok(1.0), &termination).unwrap();
+        assert_eq!(reason, StopReason::MaxGenerations);
+        assert_eq!(neat.generation, 3);
+
+        let termination = Termination {
+            max_generations: Some(100),
+            fitness_threshold: Some(0.5),
+            ..Default::default()
+        };
+        let (best, reason) = neat
+            .run_with(
+                |genome| Ok(genome.compute(vec![1.0, 1.0])?[0]),
+                &termination,
+            )
+            .unwrap();
+        assert_eq!(reason, StopReason::FitnessThreshold);
+        assert!(best.fitness >= 0.5);
+
+        let termination = Termination {
+            stagnation_limit: Some(2),
+            ..Default::default()
+        };
+        let (_, reason) = neat.run_with(|_| Ok(1.0), &termination).unwrap();
+        assert_eq!(reason, StopReason::Stagnation);
+    }
+
+    #[test]
+    fn best_genomes() {
+        let mut neat = NEAT::new(20, 2, 1, None).unwrap();
+        assert!(neat.get_best_genome().is_none());
+        assert!(neat.get_best_generation().is_none());
+
+        // the fitness goes down each generation, so the first one has the best genome
+        let mut gen_fitness = 10.0;
+        for _ in 0..3 {
+            neat.run_one_gen_with(|genome| Ok(gen_fitness + genome.id as f64 / 1000.0))
+                .unwrap();
+            gen_fitness -= 1.0;
+        }
+        let best = neat.get_best_genome().unwrap();
+        assert_eq!(best.id, 19);
+        assert_eq!(best.fitness, 10.019);
+        assert_eq!(neat.get_best_generation(), Some(0));
+        assert!(neat.get_generation_best().unwrap().fitness < 9.0);
+    }
+
+    #[test]
+    fn novelty_search() {
+        let mut neat = NEAT::new(20, 2, 1, Some(0)).unwrap();
+        let behaviour = |genome: &mut Genome| {
+            let output = genome.compute(vec![1.0, 0.0])?[0];
+            Ok((vec![output, genome.compute(vec![0.0, 1.0])?[0]], output))
+        };
+        assert!(matches!(
+            neat.run_one_gen_novelty_with(behaviour),
+            Err(NeatError::InvalidConfig(_))
+        ));
+
+        neat.set_novelty_search(Some(NoveltySearch::new(3, 0.01, 0.0).unwrap()));
+        for _ in 0..3 {
+            neat.run_one_gen_novelty_with(behaviour).unwrap();
+            assert_eq!(neat.pop.len(), 20);
+        }
+        // the fitness is kept, the novelty is what the genomes were selected on
+        for genome in &mut neat.pop {
+            let output = genome.compute(vec![1.0, 0.0]).unwrap()[0];
+            assert_eq!(genome.fitness, output);
+        }
+        let archive = neat.get_novelty_search().unwrap().get_archive();
+        assert!(!archive.is_empty());
+        assert!(archive.iter().all(|behaviour| behaviour.len() == 2));
+
+        assert!(matches!(
+            neat.run_one_gen_novelty_with(|genome| Ok((vec![genome.id as f64], 0.0))),
+            Err(NeatError::WrongVectorLength {
+                expected: 2,
+                got: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            neat.run_one_gen_novelty_with(|_| Ok((vec![0.0, f64::INFINITY], 0.0))),
+            Err(NeatError::InfiniteFitness { .. })
+        ));
+        assert!(matches!(
+            neat.run_one_gen_novelty_with(|_| Ok((vec![f64::NAN, 0.0], 0.0))),
+            Err(NeatError::NanFitness { .. })
+        ));
+        assert!(matches!(
+            neat.run_one_gen_novelty_with(|_| Ok((vec![0.0, 0.0], f64::NEG_INFINITY))),
+            Err(NeatError::InfiniteFitness { .. })
+        ));
+    }
+
+    #[test]
+    fn multiple_objectives() {
+        let mut neat = NEAT::new(30, 2, 1, Some(0)).unwrap();
+        assert!(neat.get_pareto_front().is_empty());
+        // the output on one case, and the opposite of the output on another one
+        let objectives = |genome: &mut Genome| {
+            Ok(vec![
+                genome.compute(vec![1.0, 0.0])?[0],
+                1.0 - genome.compute(vec![1.0, 1.0])?[0],
+            ])
+        };
+        for _ in 0..3 {
+            neat.run_one_gen_objectives_with(objectives).unwrap();
+            assert_eq!(neat.pop.len(), 30);
+        }
+        assert_eq!(neat.get_objectives().len(), 30);
+        let front = neat.get_pareto_front();
+        assert!(!front.is_empty());
+        for (genome, genome_objectives) in &front {
+            assert_eq!(genome.fitness, genome_objectives[0]);
+            assert!(!neat
+                .get_objectives()
+                .iter()
+                .any(|other| pareto::dominates(other, genome_objectives)));
+        }
+        // a better front always gets a better score
+        let objectives = neat.get_objectives().to_vec();
+        let scores = neat.get_objectives_scores(&objectives);
+        let fronts = pareto::get_fronts(&objectives);
+        for pair in fronts.windows(2) {
+            let worst = pair[0].iter().map(|i| scores[*i]).fold(f64::MAX, f64::min);
+            let best = pair[1].iter().map(|i| scores[*i]).fold(f64::MIN, f64::max);
+            assert!(worst > best);
+        }
+
+        assert!(matches!(
+            neat.run_one_gen_objectives_with(|genome| Ok(vec![
+                genome.id as f64;
+                1 + genome.id as usize % 2
+            ])),
+            Err(NeatError::WrongVectorLength { .. })
+        ));
+        assert!(matches!(
+            neat.run_one_gen_objectives_with(|_| Ok(vec![0.0, f64::INFINITY])),
+            Err(NeatError::InfiniteFitness { .. })
+        ));
+        assert!(matches!(
+            neat.run_one_gen_objectives_with(|_| Ok(vec![f64::NAN, 0.0])),
+            Err(NeatError::NanFitness { .. })
+        ));
+        neat.run_one_gen_with(|_| Ok(1.0)).unwrap();
+        assert!(neat.get_pareto_front().is_empty());
+    }
+
+    #[test]
+    fn real_time() {
+        let mut neat = NEAT::new(20, 2, 1, Some(0)).unwrap();
+        assert!(matches!(neat.tick(), Err(NeatError::InvalidConfig(_))));
+        assert!(matches!(
+            neat.set_fitness(20, 1.0),
+            Err(NeatError::IndexOutOfRange { index: 20, len: 20 })
+        ));
+        assert!(matches!(
+            neat.set_fitness(0, f64::NAN),
+            Err(NeatError::NanFitness { .. })
+        ));
+
+        neat.set_real_time(Some(RealTime::new(5, 8).unwrap()));
+        for i in 0..20 {
+            neat.set_fitness(i, i as f64).unwrap();
+        }
+        // the genomes are too young to be replaced at the first interval
+        for _ in 0..9 {
+            assert_eq!(neat.tick().unwrap(), None);
+        }
+        assert_eq!(neat.tick().unwrap(), Some(0));
+
+        assert_eq!(neat.pop.len(), 20);
+        assert_eq!(neat.pop[0].id, 20);
+        assert_eq!(neat.pop[0].age, 0);
+        assert!(neat.pop[1..].iter().all(|genome| genome.age == 10));
+        assert_eq!(neat.get_real_time().unwrap().get_replacements(), 1);
+        assert_eq!(neat.get_best_genome().unwrap().fitness, 19.0);
+        assert!(neat.get_pop_species_ids().iter().all(Option::is_some));
+        let n_genomes: usize = neat
+            .species
+            .iter()
+            .map(|species| species.population.len())
+            .sum();
+        assert_eq!(n_genomes, 20);
+    }
+
+    #[test]
+    fn species_ids() {
+        let mut neat = NEAT::new(30, 2, 1, Some(0)).unwrap();
+        neat.compatibility_threshold = 0.1;
+        neat.run_one_gen_with(|_| Ok(1.0)).unwrap();
+        neat.run_one_gen_with(|_| Ok(1.0)).unwrap();
+        // the ids come from their own counter, not from the genome ids
+        let ids: Vec<u32> = neat.species.iter().map(Species::get_id).collect();
+        assert!(ids.len() > 1);
+        assert!(ids.iter().all(|id| *id < neat.species_next_id));
+        assert!(ids.windows(2).all(|ids| ids[0] < ids[1]));
+    }
+
+    #[test]
+    fn compatibility_threshold() {
+        let mut neat = NEAT::new(10, 2, 1, None).unwrap();
+        let settings = get_settings();
+        let (target, step, min) = (
+            settings.target_species,
+            settings.max_difference_step,
+            settings.max_difference_min,
+        );
+        let rep_genome = neat.pop[0].clone();
+        neat.species = (0..target as u32 + 1)
+            .map(|id| Species::new(&rep_genome, id))
+            .collect();
+        neat.compatibility_threshold = 1.0;
+        neat.adjust_compatibility_threshold();
+        assert_eq!(neat.compatibility_threshold, 1.0 + step);
+
+        neat.species.truncate(1);
+        neat.compatibility_threshold = min + step / 2.0;
+        neat.adjust_compatibility_threshold();
+        assert_eq!(neat.compatibility_threshold, min);
+    }
+
+    #[test]
+    fn offspring_counts() {
+        let mut neat = NEAT::new(10, 2, 1, None).unwrap();
+        let rep_genome = neat.pop[0].clone();
+        neat.species = [(0..5, 3.0), (5..8, 1.0), (8..10, 0.0)]
+            .into_iter()
+            .enumerate()
+            .map(|(id, (individuals, total_shared_fitness))| {
+                let mut species = Species::new(&rep_genome, id as u32);
+                species.population = individuals.collect();
+                species.total_shared_fitness = total_shared_fitness;
+                species
+            })
+            .collect();
+        // 7.5 and 2.5, the offspring left goes to the first largest fractional part
+        assert_eq!(neat.get_offspring_counts(), vec![8, 2, 0]);
+
+        for species in &mut neat.species {
+            species.total_shared_fitness = 0.0;
+        }
+        assert_eq!(neat.get_offspring_counts(), vec![4, 3, 3]);
+    }
+
+    #[test]
+    fn pop_species_ids() {
+        let mut neat = NEAT::new(20, 2, 1, None).unwrap();
+        neat.run_one_gen_with(|_| Ok(1.0)).unwrap();
+        let species_ids = neat.get_pop_species_ids();
+        assert_eq!(species_ids.len(), 20);
+        for (i, species_id) in species_ids.iter().enumerate() {
+            let species = neat
+                .get_species()
+                .iter()
+                .find(|species| Some(species.get_id()) == *species_id)
+                .unwrap();
+            assert!(species.population.contains(&(i as u32)));
+            assert_eq!(species.get_age(), 1);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn checkpoint_round_trip() {
+        let mut neat = NEAT::new(30, 2, 1, None).unwrap();
+        for _ in 0..3 {
+            neat.run_one_gen_with(|genome| Ok(genome.compute(vec![1.0, 0.0])?[0]))
+                .unwrap();
+        }
+        let json = neat.to_checkpoint().to_json().unwrap();
+        let mut resumed = NEAT::from_checkpoint(Checkpoint::from_json(&json).unwrap());
+        assert_eq!(resumed.to_checkpoint().to_json().unwrap(), json);
+        assert_eq!(resumed.get_generation(), 3);
+        assert_eq!(resumed.get_pop_species_ids(), neat.get_pop_species_ids());
+
+        resumed
+            .run_one_gen_with(|genome| Ok(genome.compute(vec![1.0, 0.0])?[0]))
+            .unwrap();
+        assert_eq!(resumed.pop.len(), 30);
+        assert_eq!(resumed.get_statistics().get_generations().len(), 4);
+        assert!(resumed.pop.iter().all(|genome| genome.id >= 30));
+
+        assert!(matches!(
+            Checkpoint::from_json(&json.replace("\"version\":5", "\"version\":6")),
+            Err(NeatError::UnsupportedVersion { version: 6, .. })
+        ));
+        assert!(matches!(
+            Checkpoint::from_json(&json[..json.len() / 2]),
+            Err(NeatError::Json(_))
+        ));
+
+        // JSON has no infinity, the checkpoint couldn't be read back
+        resumed.pop[0].fitness = f64::INFINITY;
+        assert!(matches!(
+            resumed.to_checkpoint().to_json(),
+            Err(NeatError::Json(_))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn activations_are_per_run() {
+        let fitness = |genome: &mut Genome| Ok(genome.compute(vec![1.0, 0.0])?[0]);
+        let mut cppn = NEAT::new(30, 2, 1, Some(1)).unwrap();
+        cppn.set_activations(Activation::Sin, Activation::Tanh, 1.0)
+            .unwrap();
+        let mut neat = NEAT::new(30, 2, 1, Some(1)).unwrap();
+        assert_eq!(
+            neat.get_activations(),
+            Activations::from_settings(&get_settings())
+        );
+        for _ in 0..5 {
+            cppn.run_one_gen_with(fitness).unwrap();
+            neat.run_one_gen_with(fitness).unwrap();
+        }
+        assert!(neat.pop.iter().all(|genome| genome
+            .get_activations()
+            .values()
+            .all(|activation| *activation == Activation::Sigmoid)));
+        assert!(cppn
+            .pop
+            .iter()
+            .any(|genome| genome.get_activation(3) != Activation::Sigmoid));
+
+        // loading the checkpoint of a run doesn't change the activations of the others
+        let resumed = NEAT::from_checkpoint(cppn.to_checkpoint());
+        assert_eq!(resumed.get_activations(), cppn.get_activations());
+        assert_eq!(
+            neat.get_activations(),
+            Activations::from_settings(&get_settings())
+        );
+        assert_eq!(
+            NEAT::new(30, 2, 1, None).unwrap().get_activations(),
+            neat.get_activations()
+        );
+    }
+
+    /// The genomes of the population, as JSON
+    #[cfg(feature = "serde")]
+    fn get_pop_json(neat: &NEAT) -> Vec<String> {
+        neat.pop
+            .iter()
+            .map(|genome| genome.to_json().unwrap())
+            .collect()
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn same_seed_same_run() {
+        let run = |seed| {
+            let mut neat = NEAT::new(30, 2, 1, Some(seed)).unwrap();
+            for _ in 0..10 {
+                neat.run_one_gen_with(|genome| Ok(genome.compute(vec![1.0, 0.0])?[0]))
+                    .unwrap();
+            }
+            get_pop_json(&neat)
+        };
+        assert_eq!(run(42), run(42));
+        assert_ne!(run(42), run(43));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn resumed_run_is_identical() {
+        let fitness = |genome: &mut Genome| Ok(genome.compute(vec![0.5, -1.0])?[0]);
+        let mut neat = NEAT::new(30, 2, 1, Some(7)).unwrap();
+        for _ in 0..5 {
+            neat.run_one_gen_with(fitness).unwrap();
+        }
+        let json = neat.to_checkpoint().to_json().unwrap();
+        let mut resumed = NEAT::from_checkpoint(Checkpoint::from_json(&json).unwrap());
+        for _ in 0..5 {
+            neat.run_one_gen_with(fitness).unwrap();
+            resumed.run_one_gen_with(fitness).unwrap();
+        }
+        assert_eq!(get_pop_json(&neat), get_pop_json(&resumed));
+        assert_eq!(neat.get_pop_species_ids(), resumed.get_pop_species_ids());
+    }
+
+    struct CountReporter {
+        events: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl Reporter for CountReporter {
+        fn start_generation(&mut self, generation: i32) -> Result<(), NeatError> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("start {}", generation));
+            Ok(())
+        }
+
+        fn found_solution(&mut self, generation: i32, _best: &Genome) -> Result<(), NeatError> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("found {}", generation));
+            Ok(())
+        }
+
+        fn species_stagnant(
+            &mut self,
+            generation: i32,
+            species: &Species,
+        ) -> Result<(), NeatError> {
+            self.events.lock().unwrap().push(format!(
+                "stagnant {} {}",
+                generation,
+                species.get_id()
+            ));
+            Ok(())
+        }
+
+        fn end_generation(
+            &mut self,
+            generation: i32,
+            population: &[Genome],
+            _species: &[Species],
+        ) -> Result<(), NeatError> {
+            assert_eq!(population.len(), 20);
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("end {}", generation));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reporter_events() {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut neat = NEAT::new(20, 2, 1, None).unwrap();
+        neat.add_reporter(Box::new(CountReporter {
+            events: events.clone(),
+        }));
+        let termination = Termination {
+            fitness_threshold: Some(2.0),
+            ..Default::default()
+        };
+        neat.run_with(|_| Ok(2.0), &termination).unwrap();
+        assert_eq!(*events.lock().unwrap(), vec!["start 0", "end 0", "found 0"]);
+    }
+
+    #[test]
+    fn stagnant_species_are_kept() {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut neat = NEAT::new(20, 2, 1, Some(0)).unwrap();
+        neat.add_reporter(Box::new(CountReporter {
+            events: events.clone(),
+        }));
+        let limit = get_settings().species_stagnation_limit;
+        for _ in 0..limit + 2 {
+            neat.run_one_gen_with(|_| Ok(1.0)).unwrap();
+        }
+        let stagnant: Vec<u32> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| event.strip_prefix("stagnant "))
+            .map(|event| event.split(' ').nth(1).unwrap().parse().unwrap())
+            .collect();
+        assert!(!stagnant.is_empty());
+        for id in stagnant {
+            assert!(neat.species.iter().any(|species| species.get_id() == id));
         }
     }
 }
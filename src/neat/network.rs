@@ -1,19 +1,20 @@
-use super::sigmoid;
 use super::Genome;
+use super::NeatError;
 use super::Node;
 use super::NodeType;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// Network represents an individual, a network of nodes.
+/// The nodes are ordered by id, so that random choices among them are reproducible.
 pub struct Network {
-    pub nodes: HashMap<u32, Node>,
+    pub nodes: BTreeMap<u32, Node>,
     n_inputs: u32,
     n_outputs: u32,
 }
 
 impl Clone for Network {
     fn clone(&self) -> Self {
-        let mut map: HashMap<u32, Node> = HashMap::new();
+        let mut map: BTreeMap<u32, Node> = BTreeMap::new();
         map.clone_from(&self.nodes);
 
         Network {
@@ -28,7 +29,7 @@ impl Network {
     /// Creates a new Network using the genome
     pub fn new(genome: &Genome, n_inputs: u32, n_outputs: u32) -> Self {
         let mut network = Network {
-            nodes: HashMap::new(),
+            nodes: BTreeMap::new(),
             n_inputs,
             n_outputs,
         }
@@ -39,12 +40,11 @@ impl Network {
 
     /// Builds the inputs and outputs, and then the rest of the network using the genome
     fn build(self, genome: &Genome) -> Self {
-        self.build_inputs_outputs(genome).build_network(genome)
+        self.build_inputs_outputs().build_network(genome)
     }
 
     /// Creates the nodes of the network
-    fn build_inputs_outputs(mut self, genome: &Genome) -> Self {
-        self.nodes.reserve((genome.n_nodes) as usize);
+    fn build_inputs_outputs(mut self) -> Self {
         self.nodes.insert(0, Node::new(NodeType::BIAS, Some(0)));
         // bias node's value is always set to 1
         self.nodes.get_mut(&0).unwrap().value = 1.0;
@@ -95,20 +95,14 @@ impl Network {
             self.get_or_create_node(from).add_link_to(to);
             self.get_or_create_node(to).add_link_from(from, weight);
         }
+        for (id, activation) in genome.get_activations() {
+            if let Some(node) = self.nodes.get_mut(id) {
+                node.activation = *activation;
+            }
+        }
         self
     }
 
-    fn get_node(&self, id: u32) -> Option<&Node> {
-        // TODO improve:
-        // check if node ids are naturally sorted in ascending order
-        // and return None accordingly
-        self.nodes.get(&id)
-    }
-
-    fn get_node_mut(&mut self, id: u32) -> Option<&mut Node> {
-        self.nodes.get_mut(&id)
-    }
-
     fn get_or_create_node(&mut self, id: u32) -> &mut Node {
         self.nodes
             .entry(id)
@@ -142,16 +136,51 @@ impl Network {
                 node.value += pred_value * pred.weight;
             }
             node.compute_iteration = compute_iteration;
-            node.value = sigmoid(node.value);
+            node.value = node.activation.apply(node.value);
         }
         // add the node back to the map
         self.nodes.insert(id, node);
     }
 
-    pub fn set_inputs(&mut self, inputs: Vec<f64>) {
+    /**
+    Returns the depth of every node: 0 for the nodes without predecessors,
+    1 + the largest depth of its predecessors otherwise.
+    Computing the nodes by increasing depth gives the same values as `compute`.
+    */
+    pub fn get_depths(&self) -> BTreeMap<u32, usize> {
+        let mut depths = BTreeMap::new();
+        for id in self.nodes.keys() {
+            self.get_depth(*id, &mut depths);
+        }
+        depths
+    }
+
+    fn get_depth(&self, id: u32, depths: &mut BTreeMap<u32, usize>) -> usize {
+        if let Some(depth) = depths.get(&id) {
+            return *depth;
+        }
+        let node = &self.nodes[&id];
+        let depth = node
+            .pred
+            .iter()
+            .map(|pred| self.get_depth(pred.from, depths) + 1)
+            .max()
+            .unwrap_or(0);
+        depths.insert(id, depth);
+        depth
+    }
+
+    pub fn set_inputs(&mut self, inputs: Vec<f64>) -> Result<(), NeatError> {
+        if inputs.len() != self.n_inputs as usize {
+            return Err(NeatError::WrongInputLength {
+                expected: self.n_inputs as usize,
+                got: inputs.len(),
+            });
+        }
         for i in 1..=self.n_inputs {
             self.nodes.get_mut(&i).unwrap().value = inputs[(i - 1) as usize];
         }
+        Ok(())
     }
 
     pub fn get_outputs(&self) -> Vec<f64> {
@@ -185,4 +214,18 @@ mod tests {
         assert_eq!(network.nodes[&9].node_type, NodeType::OUTPUT);
         assert_eq!(network.nodes[&10].node_type, NodeType::OUTPUT);
     }
+
+    #[test]
+    fn set_inputs_wrong_length() {
+        let genome = Genome::new(0, 3, 1);
+        let mut network = Network::new(&genome, 3, 1);
+        assert!(matches!(
+            network.set_inputs(vec![1.0, 2.0]),
+            Err(NeatError::WrongInputLength {
+                expected: 3,
+                got: 2
+            })
+        ));
+        assert!(network.set_inputs(vec![1.0, 2.0, 3.0]).is_ok());
+    }
 }
@@ -1,8 +1,10 @@
+use super::Activation;
 use super::LinkFrom;
 use super::LinkTo;
 use std::fmt;
 
 #[derive(PartialEq, Debug, Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum NodeType {
     BIAS,
     INPUT,
@@ -36,6 +38,7 @@ pub struct Node {
     pub value: f64,
     pub layer: i32,
     pub compute_iteration: u32,
+    pub activation: Activation,
 }
 
 impl fmt::Display for Node {
@@ -47,14 +50,15 @@ impl fmt::Display for Node {
 
 impl Node {
     pub fn new(node_type: NodeType, layer: Option<i32>) -> Self {
-        return Node {
+        Node {
             node_type,
             succ: Vec::new(),
             pred: Vec::new(),
             value: 0.0,
             layer: layer.unwrap_or(0),
             compute_iteration: 0,
-        };
+            activation: Activation::Sigmoid,
+        }
     }
 
     pub fn add_link_to(&mut self, node_id: u32) {
@@ -0,0 +1,175 @@
+/*!
+Novelty search: genomes are rewarded for behaving differently from the others instead of for their
+objective fitness, which helps on deceptive tasks.
+
+The evaluation returns a behaviour vector for each genome. Its novelty is the mean distance to its `k`
+nearest neighbours among the population and the archive, and behaviours more novel than the threshold
+are added to the archive. The score used for selection can blend novelty with the objective fitness.
+*/
+
+use super::NeatError;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Novelty search parameters and archive, see the module documentation
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NoveltySearch {
+    /// Number of nearest neighbours used to compute the novelty
+    k: usize,
+    /// Novelty above which a behaviour is added to the archive
+    threshold: f64,
+    /// Weight of the objective fitness in the score, 0 for pure novelty search and 1 for pure fitness
+    fitness_weight: f64,
+    /// Behaviours that were novel when they were found
+    archive: Vec<Vec<f64>>,
+}
+
+/// Euclidean distance between two behaviours
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+impl NoveltySearch {
+    /// Creates a novelty search, `fitness_weight` being the weight of the fitness in the score
+    pub fn new(k: usize, threshold: f64, fitness_weight: f64) -> Result<Self, NeatError> {
+        if k == 0 {
+            return Err(NeatError::InvalidConfig(String::from(
+                "novelty search needs at least 1 neighbour",
+            )));
+        }
+        if threshold.is_nan() || threshold < 0.0 {
+            return Err(NeatError::InvalidConfig(format!(
+                "the archive threshold must be positive, got {}",
+                threshold
+            )));
+        }
+        if !(0.0..=1.0).contains(&fitness_weight) {
+            return Err(NeatError::InvalidConfig(format!(
+                "the fitness weight must be between 0 and 1, got {}",
+                fitness_weight
+            )));
+        }
+        Ok(NoveltySearch {
+            k,
+            threshold,
+            fitness_weight,
+            archive: Vec::new(),
+        })
+    }
+
+    /// Behaviours that were novel when they were found
+    pub fn get_archive(&self) -> &[Vec<f64>] {
+        &self.archive
+    }
+
+    /// Number of values of the behaviours, None until the first behaviour is archived
+    pub fn get_behaviour_length(&self) -> Option<usize> {
+        self.archive.first().map(Vec::len)
+    }
+
+    /// Mean distance of the behaviour to its `k` nearest neighbours among `others` and the archive,
+    /// 0 without neighbours
+    pub fn get_novelty<'a>(
+        &'a self,
+        behaviour: &[f64],
+        others: impl Iterator<Item = &'a Vec<f64>>,
+    ) -> f64 {
+        let mut distances: Vec<f64> = others
+            .chain(&self.archive)
+            .map(|other| distance(behaviour, other))
+            .collect();
+        distances.sort_by(f64::total_cmp);
+        let k = self.k.min(distances.len());
+        if k == 0 {
+            0.0
+        } else {
+            distances[..k].iter().sum::<f64>() / k as f64
+        }
+    }
+
+    /**
+    Returns the score of each genome of the population, from their behaviours and objective fitness.
+    The novel behaviours are archived once every genome has been scored, so that the order of the
+    population doesn't matter.
+    */
+    pub fn evaluate(&mut self, behaviours: Vec<Vec<f64>>, objective_fitness: &[f64]) -> Vec<f64> {
+        let novelty: Vec<f64> = behaviours
+            .iter()
+            .enumerate()
+            .map(|(i, behaviour)| {
+                let others = behaviours
+                    .iter()
+                    .enumerate()
+                    .filter(move |(j, _)| *j != i)
+                    .map(|(_, other)| other);
+                self.get_novelty(behaviour, others)
+            })
+            .collect();
+        for (behaviour, novelty) in behaviours.into_iter().zip(&novelty) {
+            if *novelty > self.threshold {
+                self.archive.push(behaviour);
+            }
+        }
+        novelty
+            .iter()
+            .zip(objective_fitness)
+            .map(|(novelty, fitness)| {
+                (1.0 - self.fitness_weight) * novelty + self.fitness_weight * fitness
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn novelty() {
+        let mut novelty = NoveltySearch::new(2, 1.5, 0.0).unwrap();
+        let behaviours = vec![
+            vec![0.0, 0.0],
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+            vec![5.0, 0.0],
+        ];
+        let scores = novelty.evaluate(behaviours, &[1.0; 4]);
+        // nearest neighbours of the first behaviour: at 1 and 1
+        assert_eq!(scores[0], 1.0);
+        assert_eq!(scores[1], (1.0 + 2f64.sqrt()) / 2.0);
+        assert_eq!(scores[3], (4.0 + 5.0) / 2.0);
+        assert_eq!(novelty.get_archive(), &[vec![5.0, 0.0]]);
+        assert_eq!(novelty.get_behaviour_length(), Some(2));
+
+        // the archive counts as neighbours
+        let scores = novelty.evaluate(vec![vec![5.0, 0.0], vec![0.0, 0.0]], &[2.0, 0.0]);
+        assert_eq!(scores, vec![(0.0 + 5.0) / 2.0, (5.0 + 5.0) / 2.0]);
+        assert_eq!(novelty.get_archive().len(), 3);
+    }
+
+    #[test]
+    fn overflowing_distances() {
+        let mut novelty = NoveltySearch::new(2, 1.0, 0.0).unwrap();
+        let scores = novelty.evaluate(vec![vec![f64::MAX], vec![-f64::MAX], vec![0.0]], &[0.0; 3]);
+        // the distances overflow, which the sort of the neighbours handles
+        assert_eq!(scores, vec![f64::INFINITY; 3]);
+        assert_eq!(novelty.get_archive().len(), 3);
+    }
+
+    #[test]
+    fn fitness_blend() {
+        let mut novelty = NoveltySearch::new(1, 10.0, 0.25).unwrap();
+        let scores = novelty.evaluate(vec![vec![0.0], vec![2.0]], &[4.0, 0.0]);
+        assert_eq!(scores, vec![0.75 * 2.0 + 0.25 * 4.0, 0.75 * 2.0]);
+        assert!(novelty.get_archive().is_empty());
+
+        assert!(NoveltySearch::new(0, 1.0, 0.0).is_err());
+        assert!(NoveltySearch::new(3, f64::NAN, 0.0).is_err());
+        assert!(NoveltySearch::new(3, 1.0, 1.5).is_err());
+    }
+}
@@ -0,0 +1,792 @@
+/*!
+ONNX export of genomes, along with a small interpreter for the exported models.
+
+The nodes of the network are grouped by depth, the depth of a node being 1 + the largest depth of its
+predecessors (0 for the inputs). The graph keeps a state holding the values of the inputs and of every
+node computed so far. The nodes of a depth are grouped by activation, and each group adds these operators:
+- `MatMul` of the state with the weights of the links going to the nodes of the group
+- `Add` of the weights of the links from the bias
+- the activation: `Mul` by the sigmoid steepness then `Sigmoid`, `Mul` by itself, `Neg` and `Exp` for
+  the gaussian, or `Tanh`, `Relu`, `Identity`, `Sin` or `Abs`
+
+The values of the groups are then added to the state with a `Concat`.
+
+The outputs are then selected from the state with a last `MatMul`.
+The model takes a `[batch, inputs]` tensor of doubles named `inputs`
+and returns a `[batch, outputs]` tensor named `outputs`.
+
+The interpreter only supports the operators used by the exporter.
+*/
+
+use super::get_settings;
+use super::{Activation, Genome, NeatError, Network, NodeType};
+use std::collections::{BTreeMap, HashMap};
+
+/// Version of the ONNX format written
+pub const IR_VERSION: u64 = 7;
+/// Version of the operators used
+pub const OPSET_VERSION: u64 = 13;
+/// Name of the input of the models
+pub const INPUT_NAME: &str = "inputs";
+/// Name of the output of the models
+pub const OUTPUT_NAME: &str = "outputs";
+
+/// `TensorProto.DataType.DOUBLE`
+const DOUBLE: u64 = 11;
+/// `AttributeProto.AttributeType.INT`
+const ATTRIBUTE_INT: u64 = 2;
+
+/// Protobuf message being written
+#[derive(Default)]
+struct Message {
+    data: Vec<u8>,
+}
+
+impl Message {
+    fn write_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.data.push((value as u8 & 0x7F) | 0x80);
+            value >>= 7;
+        }
+        self.data.push(value as u8);
+    }
+
+    fn varint(&mut self, field: u64, value: u64) -> &mut Self {
+        self.write_varint(field << 3);
+        self.write_varint(value);
+        self
+    }
+
+    fn bytes(&mut self, field: u64, value: &[u8]) -> &mut Self {
+        self.write_varint(field << 3 | 2);
+        self.write_varint(value.len() as u64);
+        self.data.extend_from_slice(value);
+        self
+    }
+
+    fn string(&mut self, field: u64, value: &str) -> &mut Self {
+        self.bytes(field, value.as_bytes())
+    }
+
+    fn message(&mut self, field: u64, value: &Message) -> &mut Self {
+        self.bytes(field, &value.data)
+    }
+}
+
+/// `NodeProto` of an operator
+fn node(op_type: &str, inputs: &[&str], output: &str, attributes: &[Message]) -> Message {
+    let mut node = Message::default();
+    for input in inputs {
+        node.string(1, input);
+    }
+    node.string(2, output).string(3, output).string(4, op_type);
+    for attribute in attributes {
+        node.message(5, attribute);
+    }
+    node
+}
+
+/// `TensorProto` of doubles
+fn tensor(name: &str, dims: &[usize], values: &[f64]) -> Message {
+    let mut tensor = Message::default();
+    for dim in dims {
+        tensor.varint(1, *dim as u64);
+    }
+    let raw: Vec<u8> = values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+    tensor.varint(2, DOUBLE).string(8, name).bytes(9, &raw);
+    tensor
+}
+
+/// `ValueInfoProto` of a `[batch, size]` tensor of doubles
+fn value_info(name: &str, size: usize) -> Message {
+    let mut batch = Message::default();
+    batch.string(2, "batch");
+    let mut features = Message::default();
+    features.varint(1, size as u64);
+    let mut shape = Message::default();
+    shape.message(1, &batch).message(1, &features);
+    let mut tensor_type = Message::default();
+    tensor_type.varint(1, DOUBLE).message(2, &shape);
+    let mut type_proto = Message::default();
+    type_proto.message(1, &tensor_type);
+    let mut value_info = Message::default();
+    value_info.string(1, name).message(2, &type_proto);
+    value_info
+}
+
+/// ONNX export
+impl Genome {
+    /// Returns the network as a serialized ONNX model, see the `onnx` module for the structure of the graph
+    pub fn to_onnx(&self) -> Vec<u8> {
+        let network = Network::new(self, self.get_n_inputs(), self.get_n_outputs());
+        let depths = network.get_depths();
+        let max_depth = depths.values().copied().max().unwrap_or(0);
+
+        let mut graph = Message::default();
+        graph.string(2, "genome");
+        graph.message(
+            5,
+            &tensor("steepness", &[], &[get_settings().sigmoid_steepness]),
+        );
+
+        // nodes without predecessors other than the inputs and the bias always have a value of 0,
+        // they aren't part of the state
+        let mut state: Vec<u32> = (1..=self.get_n_inputs()).collect();
+        let mut state_name = String::from(INPUT_NAME);
+        for depth in 1..=max_depth {
+            let ids: Vec<u32> = depths
+                .iter()
+                .filter(|(_, node_depth)| **node_depth == depth)
+                .map(|(id, _)| *id)
+                .collect();
+            // one product per activation, the nodes of each group are added to the state in turn
+            let mut groups: BTreeMap<Activation, Vec<u32>> = BTreeMap::new();
+            for id in ids {
+                groups
+                    .entry(network.nodes[&id].activation)
+                    .or_default()
+                    .push(id);
+            }
+            let name = |op: &str| format!("{}_{}", op, depth);
+            let mut outputs = vec![state_name.clone()];
+            let mut ids = Vec::new();
+            for (activation, group) in groups {
+                let mut weights = vec![0.0; state.len() * group.len()];
+                let mut bias = vec![0.0; group.len()];
+                for (column, id) in group.iter().enumerate() {
+                    for pred in &network.nodes[id].pred {
+                        if network.nodes[&pred.from].node_type == NodeType::BIAS {
+                            bias[column] += pred.weight;
+                        } else if let Some(row) = state.iter().position(|id| *id == pred.from) {
+                            weights[row * group.len() + column] += pred.weight;
+                        }
+                    }
+                }
+
+                let name = |op: &str| format!("{}_{}_{}", op, depth, activation.get_name());
+                graph
+                    .message(
+                        5,
+                        &tensor(&name("weights"), &[state.len(), group.len()], &weights),
+                    )
+                    .message(5, &tensor(&name("bias"), &[group.len()], &bias))
+                    .message(
+                        1,
+                        &node(
+                            "MatMul",
+                            &[&state_name, &name("weights")],
+                            &name("matmul"),
+                            &[],
+                        ),
+                    )
+                    .message(
+                        1,
+                        &node("Add", &[&name("matmul"), &name("bias")], &name("add"), &[]),
+                    );
+                let operators: Vec<(&str, Vec<String>)> = match activation {
+                    Activation::Sigmoid => vec![
+                        ("Mul", vec![name("add"), String::from("steepness")]),
+                        ("Sigmoid", vec![name("mul")]),
+                    ],
+                    Activation::Gaussian => vec![
+                        ("Mul", vec![name("add"), name("add")]),
+                        ("Neg", vec![name("mul")]),
+                        ("Exp", vec![name("neg")]),
+                    ],
+                    Activation::Tanh => vec![("Tanh", vec![name("add")])],
+                    Activation::Relu => vec![("Relu", vec![name("add")])],
+                    Activation::Identity => vec![("Identity", vec![name("add")])],
+                    Activation::Sin => vec![("Sin", vec![name("add")])],
+                    Activation::Abs => vec![("Abs", vec![name("add")])],
+                };
+                for (op_type, inputs) in &operators {
+                    let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+                    graph.message(
+                        1,
+                        &node(op_type, &inputs, &name(&op_type.to_lowercase()), &[]),
+                    );
+                }
+                outputs.push(name(&operators.last().unwrap().0.to_lowercase()));
+                ids.extend(group);
+            }
+
+            let mut axis = Message::default();
+            axis.string(1, "axis")
+                .varint(3, 1)
+                .varint(20, ATTRIBUTE_INT);
+            let outputs: Vec<&str> = outputs.iter().map(String::as_str).collect();
+            graph.message(1, &node("Concat", &outputs, &name("state"), &[axis]));
+            state.extend(ids);
+            state_name = name("state");
+        }
+
+        let n_outputs = self.get_n_outputs() as usize;
+        let mut selection = vec![0.0; state.len() * n_outputs];
+        for (column, id) in
+            (self.get_n_inputs() + 1..=self.get_n_inputs() + self.get_n_outputs()).enumerate()
+        {
+            if let Some(row) = state.iter().position(|state_id| *state_id == id) {
+                selection[row * n_outputs + column] = 1.0;
+            }
+        }
+        graph
+            .message(
+                5,
+                &tensor("selection", &[state.len(), n_outputs], &selection),
+            )
+            .message(
+                1,
+                &node("MatMul", &[&state_name, "selection"], OUTPUT_NAME, &[]),
+            )
+            .message(11, &value_info(INPUT_NAME, self.get_n_inputs() as usize))
+            .message(12, &value_info(OUTPUT_NAME, n_outputs));
+
+        let mut opset = Message::default();
+        opset.string(1, "").varint(2, OPSET_VERSION);
+        let mut model = Message::default();
+        model
+            .varint(1, IR_VERSION)
+            .string(2, "rust-neat")
+            .message(7, &graph)
+            .message(8, &opset);
+        model.data
+    }
+
+    /// Writes the ONNX model of the genome to a file
+    pub fn save_onnx(&self, path: &str) -> Result<(), NeatError> {
+        Ok(std::fs::write(path, self.to_onnx())?)
+    }
+}
+
+/// Value of a protobuf field
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Reads the fields of a protobuf message
+fn read_fields(data: &[u8]) -> Result<Vec<(u64, Value<'_>)>, NeatError> {
+    fn read_varint(data: &[u8], pos: &mut usize) -> Result<u64, NeatError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *data.get(*pos).ok_or(NeatError::Truncated {
+                expected: *pos + 1,
+                got: data.len(),
+            })?;
+            *pos += 1;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(NeatError::InvalidFormat(String::from("varint too long")))
+    }
+
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let key = read_varint(data, &mut pos)?;
+        let size = match key & 7 {
+            0 => {
+                fields.push((key >> 3, Value::Varint(read_varint(data, &mut pos)?)));
+                continue;
+            }
+            1 => 8,
+            2 => read_varint(data, &mut pos)? as usize,
+            5 => 4,
+            wire_type => {
+                return Err(NeatError::InvalidFormat(format!(
+                    "unsupported protobuf wire type {}",
+                    wire_type
+                )))
+            }
+        };
+        if data.len() - pos < size {
+            return Err(NeatError::Truncated {
+                expected: pos + size,
+                got: data.len(),
+            });
+        }
+        let value = if key & 7 == 2 {
+            Value::Bytes(&data[pos..pos + size])
+        } else {
+            Value::Fixed
+        };
+        fields.push((key >> 3, value));
+        pos += size;
+    }
+    Ok(fields)
+}
+
+fn read_string(bytes: &[u8]) -> Result<String, NeatError> {
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| NeatError::InvalidFormat(String::from("invalid UTF-8 string")))
+}
+
+/// Tensor of doubles
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tensor {
+    /// Size of each dimension
+    pub dims: Vec<usize>,
+    /// Values, the last dimension being contiguous
+    pub data: Vec<f64>,
+}
+
+impl Tensor {
+    fn parse(data: &[u8]) -> Result<(String, Self), NeatError> {
+        let mut name = String::new();
+        let mut dims = Vec::new();
+        let mut data_type = 0;
+        let mut raw: &[u8] = &[];
+        for (field, value) in read_fields(data)? {
+            match (field, value) {
+                (1, Value::Varint(dim)) => dims.push(dim as usize),
+                // packed dimensions
+                (1, Value::Bytes(bytes)) => {
+                    for (_, value) in read_fields(bytes)? {
+                        if let Value::Varint(dim) = value {
+                            dims.push(dim as usize);
+                        }
+                    }
+                }
+                (2, Value::Varint(value)) => data_type = value,
+                (8, Value::Bytes(bytes)) => name = read_string(bytes)?,
+                (9, Value::Bytes(bytes)) => raw = bytes,
+                _ => {}
+            }
+        }
+        if data_type != DOUBLE {
+            return Err(NeatError::InvalidFormat(format!(
+                "tensor {} isn't made of doubles",
+                name
+            )));
+        }
+        let size = get_size(&dims)?;
+        if size.checked_mul(8) != Some(raw.len()) {
+            return Err(NeatError::InvalidFormat(format!(
+                "tensor {} should have {} values",
+                name, size
+            )));
+        }
+        let data = raw
+            .chunks_exact(8)
+            .map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        Ok((name, Tensor { dims, data }))
+    }
+
+    fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        Tensor {
+            dims: self.dims.clone(),
+            data: self.data.iter().map(|value| f(*value)).collect(),
+        }
+    }
+
+    /// Applies `f` to the elements of both tensors, with numpy broadcasting
+    fn broadcast(&self, other: &Self, f: impl Fn(f64, f64) -> f64) -> Result<Self, NeatError> {
+        let rank = self.dims.len().max(other.dims.len());
+        let pad = |dims: &[usize]| {
+            let mut padded = vec![1; rank - dims.len()];
+            padded.extend_from_slice(dims);
+            padded
+        };
+        let (a_dims, b_dims) = (pad(&self.dims), pad(&other.dims));
+        let mut dims = Vec::with_capacity(rank);
+        for (a, b) in a_dims.iter().zip(&b_dims) {
+            if a != b && *a != 1 && *b != 1 {
+                return Err(NeatError::InvalidFormat(format!(
+                    "can't broadcast {:?} with {:?}",
+                    self.dims, other.dims
+                )));
+            }
+            dims.push(*a.max(b));
+        }
+
+        let size = get_size(&dims)?;
+        let mut data = Vec::with_capacity(size);
+        for index in 0..size {
+            // index of the element in each tensor, the broadcast dimensions staying at 0
+            let (mut rest, mut a_index, mut b_index, mut a_stride, mut b_stride) =
+                (index, 0, 0, 1, 1);
+            for axis in (0..rank).rev() {
+                let position = rest % dims[axis];
+                rest /= dims[axis];
+                if a_dims[axis] != 1 {
+                    a_index += position * a_stride;
+                }
+                if b_dims[axis] != 1 {
+                    b_index += position * b_stride;
+                }
+                a_stride *= a_dims[axis];
+                b_stride *= b_dims[axis];
+            }
+            data.push(f(self.data[a_index], other.data[b_index]));
+        }
+        Ok(Tensor { dims, data })
+    }
+
+    fn matmul(&self, other: &Self) -> Result<Self, NeatError> {
+        if self.dims.len() != 2 || other.dims.len() != 2 || self.dims[1] != other.dims[0] {
+            return Err(NeatError::InvalidFormat(format!(
+                "can't multiply {:?} by {:?}",
+                self.dims, other.dims
+            )));
+        }
+        let (m, k, n) = (self.dims[0], self.dims[1], other.dims[1]);
+        let mut data = vec![0.0; get_size(&[m, n])?];
+        for i in 0..m {
+            for j in 0..n {
+                data[i * n + j] = (0..k)
+                    .map(|l| self.data[i * k + l] * other.data[l * n + j])
+                    .sum();
+            }
+        }
+        Ok(Tensor {
+            dims: vec![m, n],
+            data,
+        })
+    }
+
+    fn concat(tensors: &[&Self], axis: i64) -> Result<Self, NeatError> {
+        let rank = tensors[0].dims.len();
+        let axis = if axis < 0 { axis + rank as i64 } else { axis } as usize;
+        if axis >= rank
+            || tensors.iter().any(|tensor| {
+                tensor.dims.len() != rank
+                    || (0..rank).any(|i| i != axis && tensor.dims[i] != tensors[0].dims[i])
+            })
+        {
+            return Err(NeatError::InvalidFormat(String::from(
+                "can't concatenate tensors of different shapes",
+            )));
+        }
+        let outer = get_size(&tensors[0].dims[..axis])?;
+        let mut dims = tensors[0].dims.clone();
+        dims[axis] = tensors
+            .iter()
+            .try_fold(0usize, |total, tensor| total.checked_add(tensor.dims[axis]))
+            .ok_or_else(|| NeatError::InvalidFormat(String::from("concatenation too large")))?;
+        let mut data = Vec::with_capacity(get_size(&dims)?);
+        for i in 0..outer {
+            for tensor in tensors {
+                let inner = get_size(&tensor.dims[axis..])?;
+                data.extend_from_slice(&tensor.data[i * inner..(i + 1) * inner]);
+            }
+        }
+        Ok(Tensor { dims, data })
+    }
+}
+
+/// Returns the number of values of a tensor with these dimensions, failing if it overflows
+fn get_size(dims: &[usize]) -> Result<usize, NeatError> {
+    dims.iter()
+        .try_fold(1usize, |size, dim| size.checked_mul(*dim))
+        .ok_or_else(|| NeatError::InvalidFormat(format!("tensor of shape {:?} is too large", dims)))
+}
+
+/// Operator of the graph
+struct Operator {
+    op_type: String,
+    inputs: Vec<String>,
+    output: String,
+    axis: i64,
+}
+
+/// ONNX model that can be run, only the operators used by `Genome::to_onnx` are supported
+pub struct Model {
+    operators: Vec<Operator>,
+    initializers: HashMap<String, Tensor>,
+    input: String,
+    input_size: usize,
+    output: String,
+}
+
+impl Model {
+    /// Reads a model, failing if it uses operators the interpreter doesn't support
+    pub fn parse(data: &[u8]) -> Result<Self, NeatError> {
+        let graph = read_fields(data)?
+            .into_iter()
+            .find_map(|(field, value)| match (field, value) {
+                (7, Value::Bytes(graph)) => Some(graph),
+                _ => None,
+            })
+            .ok_or_else(|| NeatError::InvalidFormat(String::from("the model has no graph")))?;
+
+        let mut model = Model {
+            operators: Vec::new(),
+            initializers: HashMap::new(),
+            input: String::new(),
+            input_size: 0,
+            output: String::new(),
+        };
+        for (field, value) in read_fields(graph)? {
+            let bytes = match value {
+                Value::Bytes(bytes) => bytes,
+                _ => continue,
+            };
+            match field {
+                1 => model.operators.push(Model::parse_operator(bytes)?),
+                5 => {
+                    let (name, tensor) = Tensor::parse(bytes)?;
+                    model.initializers.insert(name, tensor);
+                }
+                11 => (model.input, model.input_size) = Model::parse_value_info(bytes)?,
+                12 => model.output = Model::parse_value_info(bytes)?.0,
+                _ => {}
+            }
+        }
+        if model.input.is_empty() || model.output.is_empty() {
+            return Err(NeatError::InvalidFormat(String::from(
+                "the graph needs an input and an output",
+            )));
+        }
+        Ok(model)
+    }
+
+    fn parse_operator(data: &[u8]) -> Result<Operator, NeatError> {
+        let mut operator = Operator {
+            op_type: String::new(),
+            inputs: Vec::new(),
+            output: String::new(),
+            axis: 0,
+        };
+        for (field, value) in read_fields(data)? {
+            let bytes = match value {
+                Value::Bytes(bytes) => bytes,
+                _ => continue,
+            };
+            match field {
+                1 => operator.inputs.push(read_string(bytes)?),
+                2 => operator.output = read_string(bytes)?,
+                4 => operator.op_type = read_string(bytes)?,
+                5 => {
+                    let attribute = read_fields(bytes)?;
+                    let is_axis = attribute.iter().any(|(field, value)| {
+                        matches!(value, Value::Bytes(name) if *field == 1 && *name == b"axis")
+                    });
+                    for (field, value) in attribute {
+                        if let (true, 3, Value::Varint(axis)) = (is_axis, field, value) {
+                            operator.axis = axis as i64;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(operator)
+    }
+
+    /// Returns the name of the value and its last dimension
+    fn parse_value_info(data: &[u8]) -> Result<(String, usize), NeatError> {
+        let mut name = String::new();
+        let mut size = 0;
+        for (field, value) in read_fields(data)? {
+            match (field, value) {
+                (1, Value::Bytes(bytes)) => name = read_string(bytes)?,
+                (2, Value::Bytes(type_proto)) => {
+                    // TypeProto.tensor_type.shape.dim
+                    let mut message = type_proto;
+                    for path in [1, 2] {
+                        message = read_fields(message)?
+                            .into_iter()
+                            .find_map(|(field, value)| match value {
+                                Value::Bytes(bytes) if field == path => Some(bytes),
+                                _ => None,
+                            })
+                            .unwrap_or(&[]);
+                    }
+                    for (_, dim) in read_fields(message)? {
+                        if let Value::Bytes(dim) = dim {
+                            for (field, value) in read_fields(dim)? {
+                                if let (1, Value::Varint(value)) = (field, value) {
+                                    size = value as usize;
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok((name, size))
+    }
+
+    /// Runs the model on a batch of inputs, returns one row of outputs per row of inputs
+    pub fn run(&self, inputs: &[Vec<f64>]) -> Result<Vec<Vec<f64>>, NeatError> {
+        if let Some(row) = inputs.iter().find(|row| row.len() != self.input_size) {
+            return Err(NeatError::WrongInputLength {
+                expected: self.input_size,
+                got: row.len(),
+            });
+        }
+        let mut values: HashMap<&str, Tensor> = HashMap::new();
+        values.insert(
+            &self.input,
+            Tensor {
+                dims: vec![inputs.len(), self.input_size],
+                data: inputs.concat(),
+            },
+        );
+
+        for operator in &self.operators {
+            let operands = operator
+                .inputs
+                .iter()
+                .map(|name| {
+                    values
+                        .get(name.as_str())
+                        .or_else(|| self.initializers.get(name))
+                        .ok_or_else(|| {
+                            NeatError::InvalidFormat(format!("value {} is never defined", name))
+                        })
+                })
+                .collect::<Result<Vec<&Tensor>, NeatError>>()?;
+            let binary = |f: fn(&Tensor, &Tensor) -> Result<Tensor, NeatError>| {
+                if operands.len() != 2 {
+                    return Err(NeatError::InvalidFormat(format!(
+                        "{} takes 2 inputs",
+                        operator.op_type
+                    )));
+                }
+                f(operands[0], operands[1])
+            };
+            let result = match operator.op_type.as_str() {
+                "MatMul" => binary(Tensor::matmul)?,
+                "Add" => binary(|a, b| a.broadcast(b, |a, b| a + b))?,
+                "Mul" => binary(|a, b| a.broadcast(b, |a, b| a * b))?,
+                "Sigmoid" if operands.len() == 1 => {
+                    operands[0].map(|value| 1.0 / (1.0 + f64::exp(-value)))
+                }
+                "Tanh" if operands.len() == 1 => operands[0].map(f64::tanh),
+                "Relu" if operands.len() == 1 => operands[0].map(|value| value.max(0.0)),
+                "Identity" if operands.len() == 1 => operands[0].clone(),
+                "Sin" if operands.len() == 1 => operands[0].map(f64::sin),
+                "Abs" if operands.len() == 1 => operands[0].map(f64::abs),
+                "Neg" if operands.len() == 1 => operands[0].map(|value| -value),
+                "Exp" if operands.len() == 1 => operands[0].map(f64::exp),
+                "Concat" if !operands.is_empty() => Tensor::concat(&operands, operator.axis)?,
+                op_type => {
+                    return Err(NeatError::InvalidFormat(format!(
+                        "unsupported operator {} with {} inputs",
+                        op_type,
+                        operands.len()
+                    )))
+                }
+            };
+            values.insert(&operator.output, result);
+        }
+
+        let output = values.get(self.output.as_str()).ok_or_else(|| {
+            NeatError::InvalidFormat(format!("output {} is never computed", self.output))
+        })?;
+        let width = output.dims.last().copied().unwrap_or(1).max(1);
+        Ok(output.data.chunks(width).map(<[f64]>::to_vec).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::InnovationHistory;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn same_outputs_as_compute() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut history = InnovationHistory::new(3, 2);
+        for id in 0..10 {
+            let mut genome = Genome::new(id, 3, 2);
+            genome.mutate_weights(&mut rng);
+            for _ in 0..id * 2 {
+                genome.mutate_add_node(&mut history, &mut rng);
+                genome.mutate_add_link(&mut history, &mut rng);
+                genome.mutate_weights(&mut rng);
+            }
+            // half of the genomes use every activation
+            if id % 2 == 1 {
+                for node in 4..genome.n_nodes {
+                    let activation = Activation::ALL[(node + id) as usize % Activation::ALL.len()];
+                    genome.set_activation(node, activation).unwrap();
+                }
+            }
+            let model = Model::parse(&genome.to_onnx()).unwrap();
+
+            let inputs: Vec<Vec<f64>> = (0..20)
+                .map(|_| (0..3).map(|_| rng.gen_range(-2.0..2.0)).collect())
+                .collect();
+            let outputs = model.run(&inputs).unwrap();
+            assert_eq!(outputs.len(), 20);
+            for (inputs, outputs) in inputs.iter().zip(outputs) {
+                let expected = genome.compute(inputs.clone()).unwrap();
+                assert_eq!(outputs.len(), 2);
+                for (output, expected) in outputs.iter().zip(expected) {
+                    assert!((output - expected).abs() < 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unconnected_outputs() {
+        let mut genome = Genome::new(0, 2, 2);
+        for gene in genome.genes.iter_mut().filter(|gene| gene.to == 4) {
+            gene.enabled = false;
+        }
+        let model = Model::parse(&genome.to_onnx()).unwrap();
+        let outputs = model.run(&[vec![1.0, -1.0]]).unwrap();
+        assert_eq!(outputs, vec![genome.compute(vec![1.0, -1.0]).unwrap()]);
+        assert_eq!(outputs[0][1], 0.0);
+    }
+
+    #[test]
+    fn invalid_model() {
+        let data = Genome::new(0, 2, 1).to_onnx();
+        assert!(matches!(
+            Model::parse(&data[..data.len() - 3]),
+            Err(NeatError::Truncated { .. })
+        ));
+        assert!(matches!(
+            Model::parse(&[]),
+            Err(NeatError::InvalidFormat(_))
+        ));
+        let model = Model::parse(&data).unwrap();
+        assert!(matches!(
+            model.run(&[vec![1.0]]),
+            Err(NeatError::WrongInputLength {
+                expected: 2,
+                got: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn oversized_tensors() {
+        let parse = |dims: &[usize]| Tensor::parse(&tensor("w", dims, &[]).data);
+        assert!(matches!(
+            parse(&[1 << 32, 1 << 32]),
+            Err(NeatError::InvalidFormat(_))
+        ));
+        // the size in bytes overflows, it isn't taken for 0
+        assert!(matches!(
+            parse(&[1 << 61]),
+            Err(NeatError::InvalidFormat(_))
+        ));
+        let (_, empty) = parse(&[0, 1 << 40, 1 << 40]).unwrap();
+        assert!(empty.data.is_empty());
+
+        // the concatenated dimension overflows
+        let large = Tensor {
+            dims: vec![usize::MAX, 0],
+            data: Vec::new(),
+        };
+        assert!(matches!(
+            Tensor::concat(&[&large, &large], 0),
+            Err(NeatError::InvalidFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,131 @@
+/*!
+NSGA-II tools for runs with multiple objectives, every objective being maximized.
+
+The genomes are sorted into fronts: the first front holds the genomes no other genome dominates,
+the second one the genomes only dominated by the first front, and so on. Inside a front, the crowding
+distance favours the genomes far from the others, to keep the trade-offs between objectives diverse.
+*/
+
+/// True if `a` is at least as good as `b` on every objective, and better on at least one
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a >= b) && a.iter().zip(b).any(|(a, b)| a > b)
+}
+
+/// Sorts the indices of `objectives` into non-dominated fronts, the best front first
+pub fn get_fronts(objectives: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    let n = objectives.len();
+    // genomes dominated by each genome, and number of genomes dominating each genome
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut n_dominating = vec![0usize; n];
+    for i in 0..n {
+        for j in i + 1..n {
+            if dominates(&objectives[i], &objectives[j]) {
+                dominated[i].push(j);
+                n_dominating[j] += 1;
+            } else if dominates(&objectives[j], &objectives[i]) {
+                dominated[j].push(i);
+                n_dominating[i] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut front: Vec<usize> = (0..n).filter(|i| n_dominating[*i] == 0).collect();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for i in &front {
+            for j in &dominated[*i] {
+                n_dominating[*j] -= 1;
+                if n_dominating[*j] == 0 {
+                    next.push(*j);
+                }
+            }
+        }
+        next.sort_unstable();
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+/**
+Returns the crowding distance of each genome of `front`, in the same order: the sum over the objectives
+of the distance between its two neighbours, relative to the range of the objective.
+The genomes at the ends of an objective get an infinite distance. An objective whose range is 0, or too
+large for a f64, doesn't add to the distances.
+*/
+pub fn get_crowding_distances(objectives: &[Vec<f64>], front: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; front.len()];
+    let n_objectives = front.first().map_or(0, |i| objectives[*i].len());
+    let mut order: Vec<usize> = (0..front.len()).collect();
+    let get_values = |objective: usize| -> Vec<f64> {
+        front.iter().map(|i| objectives[*i][objective]).collect()
+    };
+    for values in (0..n_objectives).map(get_values) {
+        order.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+        let first = order[0];
+        let last = order[order.len() - 1];
+        distances[first] = f64::INFINITY;
+        distances[last] = f64::INFINITY;
+        let range = values[last] - values[first];
+        if range <= 0.0 || !range.is_finite() {
+            continue;
+        }
+        for window in order.windows(3) {
+            distances[window[1]] += (values[window[2]] - values[window[0]]) / range;
+        }
+    }
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fronts() {
+        assert!(dominates(&[1.0, 2.0], &[1.0, 1.0]));
+        assert!(!dominates(&[1.0, 1.0], &[1.0, 1.0]));
+        assert!(!dominates(&[2.0, 0.0], &[0.0, 2.0]));
+
+        let objectives = vec![
+            vec![0.0, 3.0],
+            vec![1.0, 1.0],
+            vec![3.0, 0.0],
+            vec![2.0, 2.0],
+            vec![0.0, 0.0],
+            vec![1.0, 1.0],
+        ];
+        assert_eq!(
+            get_fronts(&objectives),
+            vec![vec![0, 2, 3], vec![1, 5], vec![4]]
+        );
+        assert!(get_fronts(&[]).is_empty());
+    }
+
+    #[test]
+    fn crowding_distances() {
+        let objectives = vec![
+            vec![0.0, 4.0],
+            vec![1.0, 3.0],
+            vec![3.0, 1.0],
+            vec![4.0, 0.0],
+        ];
+        let distances = get_crowding_distances(&objectives, &[0, 1, 2, 3]);
+        assert_eq!(distances[0], f64::INFINITY);
+        assert_eq!(distances[3], f64::INFINITY);
+        assert_eq!(distances[1], 0.75 + 0.75);
+        assert_eq!(distances[2], 0.75 + 0.75);
+
+        // a single genome is at both ends
+        assert_eq!(
+            get_crowding_distances(&objectives, &[2]),
+            vec![f64::INFINITY]
+        );
+
+        // the range of the first objective overflows, only the second one counts
+        let objectives = vec![vec![-f64::MAX, 0.0], vec![0.0, 1.0], vec![f64::MAX, 2.0]];
+        let distances = get_crowding_distances(&objectives, &[0, 1, 2]);
+        assert_eq!(distances, vec![f64::INFINITY, 1.0, f64::INFINITY]);
+    }
+}
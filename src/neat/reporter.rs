@@ -0,0 +1,203 @@
+#[cfg(feature = "python")]
+use super::info::get_population_info;
+#[cfg(feature = "python")]
+use super::SpeciesInfo;
+use super::{Genome, NeatError, Species};
+#[cfg(feature = "python")]
+use pyo3::*;
+
+/**
+Receives the events of a run.
+Every method does nothing by default, implement the ones you need.
+An error returned by a reporter stops the run.
+*/
+pub trait Reporter: Send {
+    /// Called before the population of the generation is created
+    fn start_generation(&mut self, _generation: i32) -> Result<(), NeatError> {
+        Ok(())
+    }
+
+    /// Called once the population has been evaluated and split into species
+    fn post_evaluate(
+        &mut self,
+        _generation: i32,
+        _population: &[Genome],
+        _species: &[Species],
+        _best: &Genome,
+    ) -> Result<(), NeatError> {
+        Ok(())
+    }
+
+    /// Called when the fitness of a species didn't improve for `species_stagnation_limit` generations
+    fn species_stagnant(&mut self, _generation: i32, _species: &Species) -> Result<(), NeatError> {
+        Ok(())
+    }
+
+    /// Called when a run stops because a genome reached the fitness threshold
+    fn found_solution(&mut self, _generation: i32, _best: &Genome) -> Result<(), NeatError> {
+        Ok(())
+    }
+
+    /// Called at the end of the generation
+    fn end_generation(
+        &mut self,
+        _generation: i32,
+        _population: &[Genome],
+        _species: &[Species],
+    ) -> Result<(), NeatError> {
+        Ok(())
+    }
+}
+
+/// Prints the progress of the run
+#[cfg_attr(feature = "python", pyclass(module = "rust_neat"))]
+#[derive(Clone, Default)]
+pub struct StdOutReporter {}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl StdOutReporter {
+    #[new]
+    fn py_new() -> Self {
+        StdOutReporter::new()
+    }
+}
+
+impl StdOutReporter {
+    /// Creates a reporter printing to the standard output
+    pub fn new() -> Self {
+        StdOutReporter {}
+    }
+}
+
+impl Reporter for StdOutReporter {
+    fn start_generation(&mut self, generation: i32) -> Result<(), NeatError> {
+        println!("***** Generation {} *****", generation);
+        Ok(())
+    }
+
+    fn post_evaluate(
+        &mut self,
+        _generation: i32,
+        population: &[Genome],
+        _species: &[Species],
+        best: &Genome,
+    ) -> Result<(), NeatError> {
+        let mean =
+            population.iter().map(|genome| genome.fitness).sum::<f64>() / population.len() as f64;
+        println!(
+            "Mean fitness: {}, best fitness: {} (genome {})",
+            mean, best.fitness, best.id
+        );
+        Ok(())
+    }
+
+    fn species_stagnant(&mut self, _generation: i32, species: &Species) -> Result<(), NeatError> {
+        println!(
+            "Species {} didn't improve for {} generations",
+            species.get_id(),
+            species.get_stagnation()
+        );
+        Ok(())
+    }
+
+    fn found_solution(&mut self, generation: i32, best: &Genome) -> Result<(), NeatError> {
+        println!(
+            "Solution found in generation {}: genome {} with fitness {}",
+            generation, best.id, best.fitness
+        );
+        Ok(())
+    }
+
+    fn end_generation(
+        &mut self,
+        _generation: i32,
+        _population: &[Genome],
+        species: &[Species],
+    ) -> Result<(), NeatError> {
+        println!("N° of Species {}", species.len());
+        for species in species {
+            println!(
+                "Species id: {}, population: {}",
+                species.get_id(),
+                species.population.len()
+            );
+        }
+        Ok(())
+    }
+}
+
+/**
+Reporter forwarding the events to a python object.
+Only the methods defined on the object are called.
+*/
+#[cfg(feature = "python")]
+pub struct PyReporter {
+    reporter: PyObject,
+}
+
+#[cfg(feature = "python")]
+impl PyReporter {
+    pub fn new(reporter: PyObject) -> Self {
+        PyReporter { reporter }
+    }
+
+    fn call(&self, method: &str, args: impl IntoPy<Py<types::PyTuple>>) -> Result<(), NeatError> {
+        Python::with_gil(|py| {
+            let reporter = self.reporter.as_ref(py);
+            if reporter.hasattr(method)? {
+                reporter.call_method1(method, args)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(feature = "python")]
+impl Reporter for PyReporter {
+    fn start_generation(&mut self, generation: i32) -> Result<(), NeatError> {
+        self.call("start_generation", (generation,))
+    }
+
+    fn post_evaluate(
+        &mut self,
+        generation: i32,
+        population: &[Genome],
+        species: &[Species],
+        best: &Genome,
+    ) -> Result<(), NeatError> {
+        self.call(
+            "post_evaluate",
+            (
+                generation,
+                get_population_info(population, species),
+                species.iter().map(SpeciesInfo::new).collect::<Vec<_>>(),
+                best.clone(),
+            ),
+        )
+    }
+
+    fn species_stagnant(&mut self, generation: i32, species: &Species) -> Result<(), NeatError> {
+        self.call("species_stagnant", (generation, SpeciesInfo::new(species)))
+    }
+
+    fn found_solution(&mut self, generation: i32, best: &Genome) -> Result<(), NeatError> {
+        self.call("found_solution", (generation, best.clone()))
+    }
+
+    fn end_generation(
+        &mut self,
+        generation: i32,
+        population: &[Genome],
+        species: &[Species],
+    ) -> Result<(), NeatError> {
+        self.call(
+            "end_generation",
+            (
+                generation,
+                get_population_info(population, species),
+                species.iter().map(SpeciesInfo::new).collect::<Vec<_>>(),
+            ),
+        )
+    }
+}
@@ -0,0 +1,132 @@
+/*!
+rtNEAT: real-time evolution, the population evolving continuously while it is being evaluated, for
+instance by the agents of a game.
+
+The population is aged by ticks. Every `interval` ticks, the genome with the worst adjusted fitness
+among the ones that lived at least `min_age` ticks is removed, and replaced by an offspring of a species
+chosen with a probability proportional to its average fitness. Only the new genome is assigned a
+species, the others keep theirs.
+*/
+
+use super::NeatError;
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Parameters and state of real-time evolution, see the module documentation
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RealTime {
+    /// Number of ticks between two replacements
+    interval: u32,
+    /// Number of ticks a genome lives before it can be replaced
+    min_age: u32,
+    /// Ticks since the last replacement
+    ticks: u32,
+    /// Number of genomes replaced so far
+    replacements: u64,
+}
+
+impl RealTime {
+    /// Creates the real-time state, replacing a genome every `interval` ticks
+    pub fn new(interval: u32, min_age: u32) -> Result<Self, NeatError> {
+        if interval == 0 {
+            return Err(NeatError::InvalidConfig(String::from(
+                "the replacement interval must be at least 1 tick",
+            )));
+        }
+        Ok(RealTime {
+            interval,
+            min_age,
+            ticks: 0,
+            replacements: 0,
+        })
+    }
+
+    /// Number of ticks between two replacements
+    pub fn get_interval(&self) -> u32 {
+        self.interval
+    }
+
+    /// Number of ticks a genome lives before it can be replaced
+    pub fn get_min_age(&self) -> u32 {
+        self.min_age
+    }
+
+    /// Number of genomes replaced so far
+    pub fn get_replacements(&self) -> u64 {
+        self.replacements
+    }
+
+    /// Counts a tick, returns true when a genome has to be replaced
+    pub(crate) fn tick(&mut self) -> bool {
+        self.ticks += 1;
+        if self.ticks < self.interval {
+            return false;
+        }
+        self.ticks = 0;
+        true
+    }
+
+    pub(crate) fn add_replacement(&mut self) {
+        self.replacements += 1;
+    }
+}
+
+/**
+Returns the index of a species chosen with a probability proportional to its average fitness,
+negative averages counting as 0. The species are equally likely when no average is positive.
+*/
+pub fn choose_species(average_fitness: &[f64], rng: &mut impl Rng) -> usize {
+    let total: f64 = average_fitness.iter().map(|fitness| fitness.max(0.0)).sum();
+    if total <= 0.0 {
+        return rng.gen_range(0..average_fitness.len());
+    }
+    let mut target = rng.gen::<f64>() * total;
+    for (index, fitness) in average_fitness.iter().enumerate() {
+        target -= fitness.max(0.0);
+        if target < 0.0 {
+            return index;
+        }
+    }
+    // rounding errors, the last species with a positive average
+    average_fitness
+        .iter()
+        .rposition(|fitness| *fitness > 0.0)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn ticks() {
+        assert!(RealTime::new(0, 10).is_err());
+        let mut real_time = RealTime::new(3, 10).unwrap();
+        let replacements: Vec<bool> = (0..7).map(|_| real_time.tick()).collect();
+        assert_eq!(
+            replacements,
+            vec![false, false, true, false, false, true, false]
+        );
+    }
+
+    #[test]
+    fn species_choice() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut counts = [0; 3];
+        for _ in 0..1000 {
+            counts[choose_species(&[1.0, -5.0, 3.0], &mut rng)] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        assert!(counts[2] > 2 * counts[0]);
+
+        let mut counts = [0; 2];
+        for _ in 0..1000 {
+            counts[choose_species(&[0.0, -1.0], &mut rng)] += 1;
+        }
+        assert!(counts[0] > 0 && counts[1] > 0);
+    }
+}
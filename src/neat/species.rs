@@ -1,42 +1,119 @@
-use super::{Genome, SETTINGS};
+use super::Genome;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+/// Group of similar genomes, sharing their fitness and reproducing together
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Species {
     /// Representative genome
     pub rep_genome: Genome,
     id: u32,
+    /// Indices of the genomes of the species in the population
     pub population: Vec<u32>,
+    /// Sum of the adjusted fitnesses of the genomes of the species
     pub total_shared_fitness: f64,
+    /// Number of generations the species went through
+    age: u32,
+    /// Best fitness ever reached by an individual of the species, None before its first generation
+    best_fitness: Option<f64>,
+    /// Number of generations since the best fitness last improved
+    stagnation: u32,
 }
 
 impl Species {
+    /// Creates an empty species represented by the genome
     pub fn new(rep_genome: &Genome, id: u32) -> Self {
         Species {
             rep_genome: rep_genome.clone(),
             id,
             population: Vec::new(),
             total_shared_fitness: 0.0,
+            age: 0,
+            best_fitness: None,
+            stagnation: 0,
         }
     }
 
-    pub fn belongs(&self, other: &Genome) -> bool {
-        Genome::compute_difference(&self.rep_genome, other) < unsafe { SETTINGS.max_difference }
+    /// Returns true if the genome is close enough to the representative to belong to the species
+    pub fn belongs(&self, other: &Genome, max_difference: f64) -> bool {
+        Genome::compute_difference(&self.rep_genome, other) < max_difference
     }
 
+    /// Replaces the representative genome
     pub fn set_new_rep_genome(&mut self, new_rep: &Genome) {
         self.rep_genome = new_rep.clone();
     }
 
+    /// Empties the species before the genomes of the next generation are assigned to it
     pub fn prep_new_generation(&mut self) {
         self.population.clear();
         self.total_shared_fitness = 0.0;
     }
 
+    /// Ages the species by one generation, using the best fitness of its population
+    pub fn end_generation(&mut self, best_fitness: f64) {
+        self.age += 1;
+        if self.best_fitness.is_none_or(|best| best_fitness > best) {
+            self.best_fitness = Some(best_fitness);
+            self.stagnation = 0;
+        } else {
+            self.stagnation += 1;
+        }
+    }
+
+    /// Returns the id of the species of each individual of a population of `pop_size` genomes
+    pub fn get_species_ids(species: &[Species], pop_size: usize) -> Vec<Option<u32>> {
+        let mut species_ids = vec![None; pop_size];
+        for species in species {
+            for individual in &species.population {
+                species_ids[*individual as usize] = Some(species.get_id());
+            }
+        }
+        species_ids
+    }
+
+    /// Id of the species, unique in a run
     pub fn get_id(&self) -> u32 {
         self.id
     }
+
+    /// Number of generations the species went through
+    pub fn get_age(&self) -> u32 {
+        self.age
+    }
+
+    /// Best fitness ever reached in the species, None before its first generation
+    pub fn get_best_fitness(&self) -> Option<f64> {
+        self.best_fitness
+    }
+
+    /// Number of generations since the best fitness last improved
+    pub fn get_stagnation(&self) -> u32 {
+        self.stagnation
+    }
 }
 
 #[cfg(test)]
 mod species_test {
-    // use super::*;
+    use super::*;
+
+    #[test]
+    fn end_generation() {
+        let mut species = Species::new(&Genome::new(0, 2, 1), 0);
+        assert_eq!(species.get_best_fitness(), None);
+        species.end_generation(1.0);
+        assert_eq!(species.get_age(), 1);
+        assert_eq!(species.get_stagnation(), 0);
+
+        species.end_generation(0.5);
+        species.end_generation(1.0);
+        assert_eq!(species.get_age(), 3);
+        assert_eq!(species.get_stagnation(), 2);
+        assert_eq!(species.get_best_fitness(), Some(1.0));
+
+        species.end_generation(2.0);
+        assert_eq!(species.get_stagnation(), 0);
+        assert_eq!(species.get_best_fitness(), Some(2.0));
+    }
 }
@@ -0,0 +1,355 @@
+use super::{Genome, NeatError, Species};
+#[cfg(feature = "python")]
+use pyo3::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Statistics of one generation
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GenerationStats {
+    /// Generation the statistics are from
+    pub generation: i32,
+    /// Lowest fitness of the population
+    pub min_fitness: f64,
+    /// Mean fitness of the population
+    pub mean_fitness: f64,
+    /// Highest fitness of the population
+    pub max_fitness: f64,
+    /// Standard deviation of the fitness of the population
+    pub stdev_fitness: f64,
+    /// Number of genomes of each species
+    pub species_sizes: Vec<usize>,
+    /// Mean number of genes of the genomes
+    pub mean_genes: f64,
+    /// Mean number of enabled genes of the genomes
+    pub mean_enabled_genes: f64,
+    /// Mean number of hidden nodes of the genomes
+    pub mean_hidden_nodes: f64,
+    /// Compatibility threshold used to assign the species
+    pub compatibility_threshold: f64,
+    /// Time spent evaluating the population, in seconds
+    pub evaluation_time: f64,
+}
+
+impl GenerationStats {
+    /// Returns true if none of the values is NaN or infinite
+    pub fn is_finite(&self) -> bool {
+        [
+            self.min_fitness,
+            self.mean_fitness,
+            self.max_fitness,
+            self.stdev_fitness,
+            self.mean_genes,
+            self.mean_enabled_genes,
+            self.mean_hidden_nodes,
+            self.compatibility_threshold,
+            self.evaluation_time,
+        ]
+        .iter()
+        .all(|value| value.is_finite())
+    }
+
+    /// Computes the statistics of an evaluated population
+    pub fn new(
+        generation: i32,
+        population: &[Genome],
+        species: &[Species],
+        compatibility_threshold: f64,
+        evaluation_time: Duration,
+    ) -> Self {
+        let n = population.len() as f64;
+        let mean = |value: &dyn Fn(&Genome) -> f64| population.iter().map(value).sum::<f64>() / n;
+
+        let mean_fitness = mean(&|genome| genome.fitness);
+        let variance = mean(&|genome| (genome.fitness - mean_fitness).powi(2));
+        GenerationStats {
+            generation,
+            min_fitness: population
+                .iter()
+                .map(|genome| genome.fitness)
+                .fold(f64::INFINITY, f64::min),
+            mean_fitness,
+            max_fitness: population
+                .iter()
+                .map(|genome| genome.fitness)
+                .fold(f64::NEG_INFINITY, f64::max),
+            stdev_fitness: variance.sqrt(),
+            species_sizes: species
+                .iter()
+                .map(|species| species.population.len())
+                .collect(),
+            mean_genes: mean(&|genome| genome.genes.len() as f64),
+            mean_enabled_genes: mean(&|genome| {
+                genome.genes.iter().filter(|gene| gene.enabled).count() as f64
+            }),
+            mean_hidden_nodes: mean(&|genome| genome.get_nb_hidden_nodes() as f64),
+            compatibility_threshold,
+            evaluation_time: evaluation_time.as_secs_f64(),
+        }
+    }
+}
+
+/// Statistics of one generation as exported to JSON, with the number of species
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct JsonStats<'a> {
+    #[serde(flatten)]
+    stats: &'a GenerationStats,
+    n_species: usize,
+}
+
+/// Statistics of every generation of a run
+#[cfg_attr(feature = "python", pyclass(module = "rust_neat"))]
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Statistics {
+    generations: Vec<GenerationStats>,
+}
+
+impl Statistics {
+    /// Creates empty statistics
+    pub fn new() -> Self {
+        Statistics {
+            generations: Vec::new(),
+        }
+    }
+
+    /// Adds the statistics of a generation
+    pub fn record(&mut self, stats: GenerationStats) {
+        self.generations.push(stats);
+    }
+
+    /// Statistics of each generation, in order
+    pub fn get_generations(&self) -> &[GenerationStats] {
+        &self.generations
+    }
+
+    #[cfg(feature = "python")]
+    fn collect<T>(&self, value: impl Fn(&GenerationStats) -> T) -> Vec<T> {
+        self.generations.iter().map(value).collect()
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Statistics {
+    fn __len__(&self) -> usize {
+        self.generations.len()
+    }
+
+    #[getter]
+    fn generation(&self) -> Vec<i32> {
+        self.collect(|stats| stats.generation)
+    }
+
+    #[getter]
+    fn min_fitness(&self) -> Vec<f64> {
+        self.collect(|stats| stats.min_fitness)
+    }
+
+    #[getter]
+    fn mean_fitness(&self) -> Vec<f64> {
+        self.collect(|stats| stats.mean_fitness)
+    }
+
+    #[getter]
+    fn max_fitness(&self) -> Vec<f64> {
+        self.collect(|stats| stats.max_fitness)
+    }
+
+    #[getter]
+    fn stdev_fitness(&self) -> Vec<f64> {
+        self.collect(|stats| stats.stdev_fitness)
+    }
+
+    #[getter]
+    fn n_species(&self) -> Vec<usize> {
+        self.collect(|stats| stats.species_sizes.len())
+    }
+
+    #[getter]
+    fn species_sizes(&self) -> Vec<Vec<usize>> {
+        self.collect(|stats| stats.species_sizes.clone())
+    }
+
+    #[getter]
+    fn mean_genes(&self) -> Vec<f64> {
+        self.collect(|stats| stats.mean_genes)
+    }
+
+    #[getter]
+    fn mean_enabled_genes(&self) -> Vec<f64> {
+        self.collect(|stats| stats.mean_enabled_genes)
+    }
+
+    #[getter]
+    fn mean_hidden_nodes(&self) -> Vec<f64> {
+        self.collect(|stats| stats.mean_hidden_nodes)
+    }
+
+    #[getter]
+    fn compatibility_threshold(&self) -> Vec<f64> {
+        self.collect(|stats| stats.compatibility_threshold)
+    }
+
+    #[getter]
+    fn evaluation_time(&self) -> Vec<f64> {
+        self.collect(|stats| stats.evaluation_time)
+    }
+
+    #[pyo3(name = "to_csv")]
+    fn py_to_csv(&self) -> String {
+        self.to_csv()
+    }
+
+    #[cfg(feature = "serde")]
+    #[pyo3(name = "to_json")]
+    fn py_to_json(&self) -> String {
+        self.to_json()
+    }
+
+    #[pyo3(name = "save_csv")]
+    fn py_save_csv(&self, path: &str) -> Result<(), NeatError> {
+        self.save_csv(path)
+    }
+
+    #[cfg(feature = "serde")]
+    #[pyo3(name = "save_json")]
+    fn py_save_json(&self, path: &str) -> Result<(), NeatError> {
+        self.save_json(path)
+    }
+}
+
+/// Export
+impl Statistics {
+    /**
+    Returns the statistics as CSV, one line per generation.
+    The species sizes are separated by `;`
+    */
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "generation,min_fitness,mean_fitness,max_fitness,stdev_fitness,n_species,species_sizes,\
+             mean_genes,mean_enabled_genes,mean_hidden_nodes,compatibility_threshold,evaluation_time\n",
+        );
+        for stats in &self.generations {
+            let species_sizes: Vec<String> = stats
+                .species_sizes
+                .iter()
+                .map(|size| size.to_string())
+                .collect();
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{},{},{}",
+                stats.generation,
+                stats.min_fitness,
+                stats.mean_fitness,
+                stats.max_fitness,
+                stats.stdev_fitness,
+                stats.species_sizes.len(),
+                species_sizes.join(";"),
+                stats.mean_genes,
+                stats.mean_enabled_genes,
+                stats.mean_hidden_nodes,
+                stats.compatibility_threshold,
+                stats.evaluation_time,
+            )
+            .unwrap();
+        }
+        csv
+    }
+
+    /**
+    Returns the statistics as a JSON list, one object per generation.
+    JSON has no representation for NaN and infinities, they are written as null.
+    */
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        let generations: Vec<JsonStats> = self
+            .generations
+            .iter()
+            .map(|stats| JsonStats {
+                stats,
+                n_species: stats.species_sizes.len(),
+            })
+            .collect();
+        serde_json::to_string(&generations).unwrap()
+    }
+
+    /// Writes the statistics to a CSV file, see `to_csv`
+    pub fn save_csv(&self, path: &str) -> Result<(), NeatError> {
+        Ok(std::fs::write(path, self.to_csv())?)
+    }
+
+    /// Writes the statistics to a JSON file, see `to_json`
+    #[cfg(feature = "serde")]
+    pub fn save_json(&self, path: &str) -> Result<(), NeatError> {
+        Ok(std::fs::write(path, self.to_json())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_statistics() -> Statistics {
+        let mut population = vec![Genome::new(0, 2, 1), Genome::new(1, 2, 1)];
+        population[0].fitness = 1.0;
+        population[1].fitness = 3.0;
+        let mut species = Species::new(&population[0], 0);
+        species.population = vec![0, 1];
+
+        let mut statistics = Statistics::new();
+        statistics.record(GenerationStats::new(
+            0,
+            &population,
+            &[species],
+            3.0,
+            Duration::from_millis(500),
+        ));
+        statistics
+    }
+
+    #[test]
+    fn generation_stats() {
+        let statistics = get_statistics();
+        let stats = &statistics.get_generations()[0];
+        assert_eq!(stats.min_fitness, 1.0);
+        assert_eq!(stats.mean_fitness, 2.0);
+        assert_eq!(stats.max_fitness, 3.0);
+        assert_eq!(stats.stdev_fitness, 1.0);
+        assert_eq!(stats.species_sizes, vec![2]);
+        assert_eq!(stats.mean_genes, 2.0);
+        assert_eq!(stats.mean_hidden_nodes, 0.0);
+        assert_eq!(stats.evaluation_time, 0.5);
+    }
+
+    #[test]
+    fn export() {
+        let statistics = get_statistics();
+        let csv = statistics.to_csv();
+        assert_eq!(csv.lines().count(), 2);
+        assert_eq!(csv.lines().nth(1).unwrap(), "0,1,2,3,1,1,2,2,2,0,3,0.5");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn export_json() {
+        let mut statistics = get_statistics();
+        let json: serde_json::Value = serde_json::from_str(&statistics.to_json()).unwrap();
+        let expected: serde_json::Value = serde_json::from_str(
+            "[{\"generation\":0,\"min_fitness\":1.0,\"mean_fitness\":2.0,\"max_fitness\":3.0,\
+             \"stdev_fitness\":1.0,\"n_species\":1,\"species_sizes\":[2],\"mean_genes\":2.0,\
+             \"mean_enabled_genes\":2.0,\"mean_hidden_nodes\":0.0,\"compatibility_threshold\":3.0,\
+             \"evaluation_time\":0.5}]",
+        )
+        .unwrap();
+        assert_eq!(json, expected);
+
+        statistics.generations[0].stdev_fitness = f64::NAN;
+        let json: serde_json::Value = serde_json::from_str(&statistics.to_json()).unwrap();
+        assert!(json[0]["stdev_fitness"].is_null());
+    }
+}
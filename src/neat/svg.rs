@@ -0,0 +1,165 @@
+/*!
+SVG rendering of genomes, for when Graphviz isn't available.
+Each layer of the network is a column, the nodes of a layer being stacked by id.
+The connections use the same colors and widths as the DOT export, disabled genes aren't drawn.
+*/
+
+use super::dot::{edge_color, edge_width, get_max_weight, node_color};
+use super::{Genome, NeatError, Network, Node, NodeType};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const MARGIN: f64 = 40.0;
+const LAYER_SPACING: f64 = 160.0;
+const NODE_SPACING: f64 = 70.0;
+const NODE_RADIUS: f64 = 20.0;
+
+/// Name of the activation of the node, inputs and bias only pass their value along
+fn activation_label(node: &Node) -> &'static str {
+    match node.node_type {
+        NodeType::BIAS => "bias",
+        NodeType::INPUT => "input",
+        NodeType::HIDDEN | NodeType::OUTPUT => node.activation.get_name(),
+    }
+}
+
+/// Visualization
+impl Genome {
+    /// Returns a self-contained SVG image of the network
+    pub fn to_svg(&self) -> String {
+        let network = Network::new(self, self.get_n_inputs(), self.get_n_outputs());
+        let max_weight = get_max_weight(self);
+
+        let mut layers: BTreeMap<i32, Vec<u32>> = BTreeMap::new();
+        for (id, node) in &network.nodes {
+            layers.entry(node.layer).or_default().push(*id);
+        }
+        let max_nodes = layers.values().map(Vec::len).max().unwrap_or(0);
+        let height = 2.0 * MARGIN + (max_nodes.max(1) - 1) as f64 * NODE_SPACING;
+        let width = 2.0 * MARGIN + (layers.len().max(1) - 1) as f64 * LAYER_SPACING;
+
+        // each layer is centered vertically
+        let mut positions: BTreeMap<u32, (f64, f64)> = BTreeMap::new();
+        for (column, ids) in layers.values().enumerate() {
+            let top = (height - (ids.len() - 1) as f64 * NODE_SPACING) / 2.0;
+            for (row, id) in ids.iter().enumerate() {
+                positions.insert(
+                    *id,
+                    (
+                        MARGIN + column as f64 * LAYER_SPACING,
+                        top + row as f64 * NODE_SPACING,
+                    ),
+                );
+            }
+        }
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = width + 2.0 * NODE_RADIUS,
+            h = height + 2.0 * NODE_RADIUS,
+        )
+        .unwrap();
+        svg.push_str(
+            "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" \
+             markerWidth=\"5\" markerHeight=\"5\" orient=\"auto-start-reverse\">\
+             <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"context-stroke\"/></marker></defs>\n",
+        );
+        writeln!(
+            svg,
+            "<g transform=\"translate({r},{r})\" font-family=\"sans-serif\" text-anchor=\"middle\">",
+            r = NODE_RADIUS
+        )
+        .unwrap();
+
+        for gene in self.genes.iter().filter(|gene| gene.enabled) {
+            let (x1, y1) = positions[&gene.from];
+            let (x2, y2) = positions[&gene.to];
+            // the line stops at the border of the circles
+            let length = f64::hypot(x2 - x1, y2 - y1);
+            let (dx, dy) = (
+                (x2 - x1) / length * NODE_RADIUS,
+                (y2 - y1) / length * NODE_RADIUS,
+            );
+            writeln!(
+                svg,
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" \
+                 stroke-width=\"{:.2}\" marker-end=\"url(#arrow)\"><title>{} -> {}: {:.4}</title></line>",
+                x1 + dx,
+                y1 + dy,
+                x2 - dx,
+                y2 - dy,
+                edge_color(gene.weight),
+                edge_width(gene.weight, max_weight),
+                gene.from,
+                gene.to,
+                gene.weight
+            )
+            .unwrap();
+        }
+
+        for (id, (x, y)) in &positions {
+            let node = &network.nodes[id];
+            writeln!(
+                svg,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"{}\" stroke=\"#424242\"/>\
+                 <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\">{}</text>\
+                 <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"8\">{}</text>",
+                x,
+                y,
+                NODE_RADIUS,
+                node_color(&node.node_type),
+                x,
+                y + 1.0,
+                id,
+                x,
+                y + 10.0,
+                activation_label(node)
+            )
+            .unwrap();
+        }
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+
+    /// Writes the SVG drawing of the network to a file
+    pub fn save_svg(&self, path: &str) -> Result<(), NeatError> {
+        Ok(std::fs::write(path, self.to_svg())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::{Activation, InnovationHistory};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn to_svg() {
+        let mut history = InnovationHistory::new(2, 1);
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut genome = Genome::new(0, 2, 1);
+        genome.genes[0].weight = 1.0;
+        genome.mutate_add_node(&mut history, &mut rng);
+
+        let svg = genome.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        // bias, 2 inputs, 1 hidden and 1 output node, 3 enabled genes
+        assert_eq!(svg.matches("<circle").count(), 5);
+        assert_eq!(svg.matches("<line").count(), 3);
+        assert_eq!(svg.matches(">sigmoid<").count(), 2);
+        assert_eq!(svg.matches(">input<").count(), 2);
+        // the hidden node is in the second column and the output in the third
+        let x = MARGIN + LAYER_SPACING;
+        assert!(svg.contains(&format!("<circle cx=\"{:.1}\"", x)));
+        assert!(svg.contains(&format!("<circle cx=\"{:.1}\"", x + LAYER_SPACING)));
+
+        genome.set_activation(4, Activation::Gaussian).unwrap();
+        let svg = genome.to_svg();
+        assert_eq!(svg.matches(">sigmoid<").count(), 1);
+        assert_eq!(svg.matches(">gaussian<").count(), 1);
+    }
+}
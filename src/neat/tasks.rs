@@ -0,0 +1,295 @@
+/*!
+Built-in benchmark tasks, used as sanity checks of the algorithm.
+
+The fitness of the supervised tasks is the number of cases minus the sum of the squared errors of the
+outputs, so that a network always answering 0.5 gets three quarters of the maximum fitness on binary tasks.
+The fitness of the pole balancing tasks is the number of steps the poles stayed up.
+*/
+
+use super::{Genome, NeatError};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::f64::consts::PI;
+
+mod cart_pole;
+pub use cart_pole::{CartPole, CartPoleState, Poles};
+
+/// A problem the networks can be evolved on
+pub trait Task {
+    /// Number of inputs of the networks
+    fn get_n_inputs(&self) -> usize;
+
+    /// Number of outputs of the networks
+    fn get_n_outputs(&self) -> usize;
+
+    /// Fitness above which the task is considered solved
+    fn get_fitness_threshold(&self) -> f64;
+
+    /// Fitness of the genome, the higher the better
+    fn evaluate(&self, genome: &mut Genome) -> Result<f64, NeatError>;
+}
+
+/// Allowed squared error per case on the binary tasks
+const BINARY_TOLERANCE: f64 = 0.025;
+
+/**
+Returns the task with this name: `xor`, `parity` (3 bits) or `parityN`, `sine`, `recall`,
+or the pole balancing tasks `pole`, `double-pole`, with `-nv` for the variants without velocities.
+The `-nv` variants are much harder for feed-forward networks, see `CartPole`.
+*/
+pub fn from_name(name: &str) -> Result<Box<dyn Task>, NeatError> {
+    match name {
+        "xor" => Ok(Box::new(Xor)),
+        "sine" => Ok(Box::new(SineRegression::new(16))),
+        "recall" => Ok(Box::new(DelayedRecall::new(4, 2, 32, 0)?)),
+        "parity" => Ok(Box::new(Parity::new(3)?)),
+        "pole" => Ok(Box::new(CartPole::new(Poles::Single, true, 10_000))),
+        "pole-nv" => Ok(Box::new(CartPole::new(Poles::Single, false, 10_000))),
+        "double-pole" => Ok(Box::new(CartPole::new(Poles::Double, true, 100_000))),
+        "double-pole-nv" => Ok(Box::new(CartPole::new(Poles::Double, false, 100_000))),
+        _ => match name.strip_prefix("parity").map(str::parse) {
+            Some(Ok(bits)) => Ok(Box::new(Parity::new(bits)?)),
+            _ => Err(NeatError::InvalidConfig(format!(
+                "unknown task '{}', expected 'xor', 'parity', 'parityN', 'sine', 'recall', \
+                 'pole', 'pole-nv', 'double-pole' or 'double-pole-nv'",
+                name
+            ))),
+        },
+    }
+}
+
+/// Sum of the squared errors of the first output on every case
+fn squared_error(genome: &mut Genome, cases: &[(Vec<f64>, f64)]) -> Result<f64, NeatError> {
+    let mut error = 0.0;
+    for (inputs, expected) in cases {
+        error += (genome.compute(inputs.clone())?[0] - expected).powi(2);
+    }
+    Ok(error)
+}
+
+/// Exclusive or of 2 inputs
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Xor;
+
+impl Task for Xor {
+    fn get_n_inputs(&self) -> usize {
+        2
+    }
+
+    fn get_n_outputs(&self) -> usize {
+        1
+    }
+
+    fn get_fitness_threshold(&self) -> f64 {
+        4.0 * (1.0 - BINARY_TOLERANCE)
+    }
+
+    fn evaluate(&self, genome: &mut Genome) -> Result<f64, NeatError> {
+        let cases = [
+            (vec![0.0, 0.0], 0.0),
+            (vec![0.0, 1.0], 1.0),
+            (vec![1.0, 0.0], 1.0),
+            (vec![1.0, 1.0], 0.0),
+        ];
+        Ok(4.0 - squared_error(genome, &cases)?)
+    }
+}
+
+/// Parity of N bits: 1 if an odd number of inputs are 1, evaluated on every combination
+#[derive(Clone, Debug)]
+pub struct Parity {
+    cases: Vec<(Vec<f64>, f64)>,
+}
+
+impl Parity {
+    /// Creates the parity task of `bits` bits, from 1 to 16
+    pub fn new(bits: usize) -> Result<Self, NeatError> {
+        if !(1..=16).contains(&bits) {
+            return Err(NeatError::InvalidConfig(format!(
+                "parity needs between 1 and 16 bits, got {}",
+                bits
+            )));
+        }
+        let cases = (0..1u32 << bits)
+            .map(|case| {
+                let inputs = (0..bits).map(|bit| ((case >> bit) & 1) as f64).collect();
+                (inputs, (case.count_ones() % 2) as f64)
+            })
+            .collect();
+        Ok(Parity { cases })
+    }
+}
+
+impl Task for Parity {
+    fn get_n_inputs(&self) -> usize {
+        self.cases[0].0.len()
+    }
+
+    fn get_n_outputs(&self) -> usize {
+        1
+    }
+
+    fn get_fitness_threshold(&self) -> f64 {
+        self.cases.len() as f64 * (1.0 - BINARY_TOLERANCE)
+    }
+
+    fn evaluate(&self, genome: &mut Genome) -> Result<f64, NeatError> {
+        Ok(self.cases.len() as f64 - squared_error(genome, &self.cases)?)
+    }
+}
+
+/**
+Regression of `sin(x)` over `[-π, π]`.
+The input is `x / π` and the expected output is `(sin(x) + 1) / 2`, so that it fits in the sigmoid.
+*/
+#[derive(Clone, Debug)]
+pub struct SineRegression {
+    cases: Vec<(Vec<f64>, f64)>,
+}
+
+impl SineRegression {
+    /// The samples are evenly spaced, `samples` must be at least 2
+    pub fn new(samples: usize) -> Self {
+        let samples = samples.max(2);
+        let cases = (0..samples)
+            .map(|i| {
+                let x = -1.0 + 2.0 * i as f64 / (samples - 1) as f64;
+                (vec![x], ((x * PI).sin() + 1.0) / 2.0)
+            })
+            .collect();
+        SineRegression { cases }
+    }
+}
+
+impl Task for SineRegression {
+    fn get_n_inputs(&self) -> usize {
+        1
+    }
+
+    fn get_n_outputs(&self) -> usize {
+        1
+    }
+
+    /// Squared error of 0.01 per sample, the outputs being in `[0, 1]`
+    fn get_fitness_threshold(&self) -> f64 {
+        self.cases.len() as f64 * 0.99
+    }
+
+    fn evaluate(&self, genome: &mut Genome) -> Result<f64, NeatError> {
+        Ok(self.cases.len() as f64 - squared_error(genome, &self.cases)?)
+    }
+}
+
+/**
+Recall of a random bit sequence, `delay` steps later.
+The networks have no recurrent connections, so the last `window` bits of the sequence are given
+as inputs (the current one first), and the output must be the bit seen `delay` steps ago.
+The sequence is generated from `seed`, so the task is the same for every genome.
+*/
+#[derive(Clone, Debug)]
+pub struct DelayedRecall {
+    window: usize,
+    cases: Vec<(Vec<f64>, f64)>,
+}
+
+impl DelayedRecall {
+    /// Creates the task from `length` random bits generated from the seed
+    pub fn new(window: usize, delay: usize, length: usize, seed: u64) -> Result<Self, NeatError> {
+        if delay >= window {
+            return Err(NeatError::InvalidConfig(format!(
+                "the delay ({}) must be smaller than the window ({})",
+                delay, window
+            )));
+        }
+        if length < window {
+            return Err(NeatError::InvalidConfig(format!(
+                "the sequence ({} bits) must be at least as long as the window ({})",
+                length, window
+            )));
+        }
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let sequence: Vec<f64> = (0..length)
+            .map(|_| if rng.gen::<bool>() { 1.0 } else { 0.0 })
+            .collect();
+        let cases = (window - 1..length)
+            .map(|step| {
+                let inputs = (0..window).map(|age| sequence[step - age]).collect();
+                (inputs, sequence[step - delay])
+            })
+            .collect();
+        Ok(DelayedRecall { window, cases })
+    }
+}
+
+impl Task for DelayedRecall {
+    fn get_n_inputs(&self) -> usize {
+        self.window
+    }
+
+    fn get_n_outputs(&self) -> usize {
+        1
+    }
+
+    fn get_fitness_threshold(&self) -> f64 {
+        self.cases.len() as f64 * (1.0 - BINARY_TOLERANCE)
+    }
+
+    fn evaluate(&self, genome: &mut Genome) -> Result<f64, NeatError> {
+        Ok(self.cases.len() as f64 - squared_error(genome, &self.cases)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Genomes start with every weight at 0, so they always answer 0.5
+    fn get_blank_fitness(task: &dyn Task) -> f64 {
+        let mut genome = Genome::new(0, task.get_n_inputs() as u32, task.get_n_outputs() as u32);
+        task.evaluate(&mut genome).unwrap()
+    }
+
+    #[test]
+    fn binary_tasks() {
+        assert_eq!(get_blank_fitness(&Xor), 3.0);
+        let parity = Parity::new(4).unwrap();
+        assert_eq!(parity.get_n_inputs(), 4);
+        assert_eq!(get_blank_fitness(&parity), 12.0);
+        assert_eq!(parity.cases.iter().filter(|case| case.1 == 1.0).count(), 8);
+        assert!(Parity::new(0).is_err());
+
+        let recall = DelayedRecall::new(4, 2, 20, 0).unwrap();
+        assert_eq!(recall.cases.len(), 17);
+        assert_eq!(get_blank_fitness(&recall), 17.0 * 0.75);
+        for case in &recall.cases {
+            assert_eq!(case.0[2], case.1);
+        }
+        assert!(DelayedRecall::new(2, 2, 20, 0).is_err());
+    }
+
+    #[test]
+    fn sine_regression() {
+        let sine = SineRegression::new(5);
+        let inputs: Vec<f64> = sine.cases.iter().map(|case| case.0[0]).collect();
+        assert_eq!(inputs, vec![-1.0, -0.5, 0.0, 0.5, 1.0]);
+        assert!((sine.cases[1].1 - 0.0).abs() < 1e-12);
+        assert!((sine.cases[3].1 - 1.0).abs() < 1e-12);
+        assert!((get_blank_fitness(&sine) - 4.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn task_names() {
+        assert_eq!(from_name("xor").unwrap().get_n_inputs(), 2);
+        assert_eq!(from_name("parity").unwrap().get_n_inputs(), 3);
+        assert_eq!(from_name("parity5").unwrap().get_n_inputs(), 5);
+        assert_eq!(from_name("recall").unwrap().get_n_inputs(), 4);
+        assert!(matches!(
+            from_name("parityx"),
+            Err(NeatError::InvalidConfig(_))
+        ));
+        assert_eq!(from_name("pole-nv").unwrap().get_n_inputs(), 2);
+        assert_eq!(from_name("double-pole").unwrap().get_n_inputs(), 6);
+        assert_eq!(from_name("double-pole-nv").unwrap().get_n_inputs(), 3);
+        assert!(from_name("pendulum").is_err());
+    }
+}
@@ -0,0 +1,324 @@
+/*!
+Cart-pole balancing: a cart on a track of 4.8 m holds one or two poles, hinged on its top,
+which have to be kept up by pushing the cart left or right.
+
+The single pole follows the equations of Barto, Sutton and Anderson (1983), integrated with Euler's
+method every 0.02 s. The double pole follows Wieland (1991) with the constants used in the NEAT paper:
+poles of 1 m and 0.1 m, fourth order Runge-Kutta with steps of 0.01 s, two steps per action.
+*/
+
+use super::Task;
+use crate::neat::{Genome, NeatError};
+
+const GRAVITY: f64 = 9.8;
+const CART_MASS: f64 = 1.0;
+const FORCE_MAG: f64 = 10.0;
+const TRACK_LIMIT: f64 = 2.4;
+/// Friction of the hinges of the double pole
+const POLE_FRICTION: f64 = 0.000002;
+
+const SINGLE_POLE_MASS: f64 = 0.1;
+const SINGLE_POLE_HALF_LENGTH: f64 = 0.5;
+const SINGLE_TAU: f64 = 0.02;
+/// 12 degrees
+const SINGLE_FAILURE_ANGLE: f64 = 12.0 * std::f64::consts::PI / 180.0;
+
+const DOUBLE_POLE_MASSES: [f64; 2] = [0.1, 0.01];
+const DOUBLE_POLE_HALF_LENGTHS: [f64; 2] = [0.5, 0.05];
+const DOUBLE_TAU: f64 = 0.01;
+/// 36 degrees
+const DOUBLE_FAILURE_ANGLE: f64 = 36.0 * std::f64::consts::PI / 180.0;
+
+/// Initial angle of the (long) pole, 4.5 degrees
+const INITIAL_ANGLE: f64 = 4.5 * std::f64::consts::PI / 180.0;
+
+/// Number of poles on the cart
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Poles {
+    /// A single pole
+    Single,
+    /// Two poles of different lengths
+    Double,
+}
+
+/**
+State of the cart and its poles.
+Positions are in meters and angles in radians, 0 being upright.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct CartPoleState {
+    /// Number of poles on the cart
+    pub poles: Poles,
+    /// Position of the cart
+    pub x: f64,
+    /// Velocity of the cart
+    pub x_velocity: f64,
+    /// Angles of the poles, the second one is always 0 with a single pole
+    pub angles: [f64; 2],
+    /// Angular velocities of the poles
+    pub angular_velocities: [f64; 2],
+}
+
+impl CartPoleState {
+    /// The cart is at the center of the track, the long pole leaning by 4.5 degrees
+    pub fn new(poles: Poles) -> Self {
+        CartPoleState {
+            poles,
+            x: 0.0,
+            x_velocity: 0.0,
+            angles: [INITIAL_ANGLE, 0.0],
+            angular_velocities: [0.0, 0.0],
+        }
+    }
+
+    /// Moves the simulation forward by one action, `force` being clamped to ±10 N
+    pub fn step(&mut self, force: f64) {
+        let force = force.clamp(-FORCE_MAG, FORCE_MAG);
+        match self.poles {
+            Poles::Single => self.step_single(force),
+            Poles::Double => {
+                for _ in 0..2 {
+                    self.step_double(force);
+                }
+            }
+        }
+    }
+
+    /// True once the cart left the track or a pole fell too far
+    pub fn is_failed(&self) -> bool {
+        let failure_angle = self.get_failure_angle();
+        self.x.abs() > TRACK_LIMIT || self.angles.iter().any(|angle| angle.abs() > failure_angle)
+    }
+
+    fn get_failure_angle(&self) -> f64 {
+        match self.poles {
+            Poles::Single => SINGLE_FAILURE_ANGLE,
+            Poles::Double => DOUBLE_FAILURE_ANGLE,
+        }
+    }
+
+    /**
+    Inputs given to the networks, scaled to about `[-1, 1]`.
+    Without velocities, only the position of the cart and the angles of the poles are given.
+    */
+    pub fn get_observation(&self, velocities: bool) -> Vec<f64> {
+        let n_poles = match self.poles {
+            Poles::Single => 1,
+            Poles::Double => 2,
+        };
+        let mut observation = vec![self.x / TRACK_LIMIT];
+        if velocities {
+            observation.push(self.x_velocity / 2.0);
+        }
+        for pole in 0..n_poles {
+            observation.push(self.angles[pole] / self.get_failure_angle());
+            if velocities {
+                observation.push(self.angular_velocities[pole] / 2.0);
+            }
+        }
+        observation
+    }
+
+    fn step_single(&mut self, force: f64) {
+        let total_mass = CART_MASS + SINGLE_POLE_MASS;
+        let pole_mass_length = SINGLE_POLE_MASS * SINGLE_POLE_HALF_LENGTH;
+        let (sin, cos) = self.angles[0].sin_cos();
+
+        let temp =
+            (force + pole_mass_length * self.angular_velocities[0].powi(2) * sin) / total_mass;
+        let angular_acceleration = (GRAVITY * sin - cos * temp)
+            / (SINGLE_POLE_HALF_LENGTH * (4.0 / 3.0 - SINGLE_POLE_MASS * cos * cos / total_mass));
+        let acceleration = temp - pole_mass_length * angular_acceleration * cos / total_mass;
+
+        self.x += SINGLE_TAU * self.x_velocity;
+        self.x_velocity += SINGLE_TAU * acceleration;
+        self.angles[0] += SINGLE_TAU * self.angular_velocities[0];
+        self.angular_velocities[0] += SINGLE_TAU * angular_acceleration;
+    }
+
+    /// State as `[x, x', angle 1, angle 1', angle 2, angle 2']`
+    fn get_double_state(&self) -> [f64; 6] {
+        [
+            self.x,
+            self.x_velocity,
+            self.angles[0],
+            self.angular_velocities[0],
+            self.angles[1],
+            self.angular_velocities[1],
+        ]
+    }
+
+    /// Derivatives of the double pole state
+    fn get_double_derivatives(force: f64, state: &[f64; 6]) -> [f64; 6] {
+        let mut effective_force = 0.0;
+        let mut effective_mass = 0.0;
+        let mut terms = [(0.0, 0.0, 0.0); 2];
+        for pole in 0..2 {
+            let mass = DOUBLE_POLE_MASSES[pole];
+            let half_length = DOUBLE_POLE_HALF_LENGTHS[pole];
+            let (angle, angular_velocity) = (state[2 + 2 * pole], state[3 + 2 * pole]);
+            let (sin, cos) = angle.sin_cos();
+            let gravity_sin = -GRAVITY * sin;
+            let friction = POLE_FRICTION * angular_velocity / (half_length * mass);
+
+            effective_force += half_length * mass * angular_velocity.powi(2) * sin
+                + 0.75 * mass * cos * (friction + gravity_sin);
+            effective_mass += mass * (1.0 - 0.75 * cos * cos);
+            terms[pole] = (cos, gravity_sin, friction);
+        }
+        let acceleration = (force + effective_force) / (effective_mass + CART_MASS);
+
+        let mut derivatives = [state[1], acceleration, state[3], 0.0, state[5], 0.0];
+        for (pole, (cos, gravity_sin, friction)) in terms.iter().enumerate() {
+            derivatives[3 + 2 * pole] = -0.75 * (acceleration * cos + gravity_sin + friction)
+                / DOUBLE_POLE_HALF_LENGTHS[pole];
+        }
+        derivatives
+    }
+
+    /// One step of fourth order Runge-Kutta
+    fn step_double(&mut self, force: f64) {
+        let state = self.get_double_state();
+        let offset = |derivatives: &[f64; 6], factor: f64| {
+            let mut next = state;
+            for (value, derivative) in next.iter_mut().zip(derivatives) {
+                *value += factor * derivative;
+            }
+            next
+        };
+        let k1 = Self::get_double_derivatives(force, &state);
+        let k2 = Self::get_double_derivatives(force, &offset(&k1, DOUBLE_TAU / 2.0));
+        let k3 = Self::get_double_derivatives(force, &offset(&k2, DOUBLE_TAU / 2.0));
+        let k4 = Self::get_double_derivatives(force, &offset(&k3, DOUBLE_TAU));
+
+        let mut next = state;
+        for i in 0..6 {
+            next[i] += DOUBLE_TAU / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+        }
+        self.x = next[0];
+        self.x_velocity = next[1];
+        self.angles = [next[2], next[4]];
+        self.angular_velocities = [next[3], next[5]];
+    }
+}
+
+/**
+Pole balancing task: the fitness is the number of steps before a failure, up to `max_steps`.
+The network has one output, mapped to a force from -10 N (0) to 10 N (1).
+The Markovian variant gives the velocities as inputs, the non-Markovian one only gives the positions,
+so the networks have to estimate the velocities themselves. The networks have no recurrent
+connections and only see the current positions, so they can't: the non-Markovian single pole is
+much harder than the Markovian one, and the non-Markovian double pole is practically unsolvable.
+*/
+#[derive(Clone, Debug)]
+pub struct CartPole {
+    /// Number of poles on the cart
+    pub poles: Poles,
+    /// Whether the velocities are given as inputs
+    pub markovian: bool,
+    /// Number of steps the poles have to be balanced for
+    pub max_steps: usize,
+}
+
+impl CartPole {
+    /// Creates the task
+    pub fn new(poles: Poles, markovian: bool, max_steps: usize) -> Self {
+        CartPole {
+            poles,
+            markovian,
+            max_steps,
+        }
+    }
+
+    /// Runs an episode, returning the number of steps the poles were balanced
+    pub fn simulate(&self, genome: &mut Genome) -> Result<usize, NeatError> {
+        let mut state = CartPoleState::new(self.poles);
+        for step in 0..self.max_steps {
+            let output = genome.compute(state.get_observation(self.markovian))?[0];
+            state.step(FORCE_MAG * (2.0 * output - 1.0));
+            if state.is_failed() {
+                return Ok(step);
+            }
+        }
+        Ok(self.max_steps)
+    }
+}
+
+impl Task for CartPole {
+    fn get_n_inputs(&self) -> usize {
+        let n_poles = match self.poles {
+            Poles::Single => 1,
+            Poles::Double => 2,
+        };
+        if self.markovian {
+            2 + 2 * n_poles
+        } else {
+            1 + n_poles
+        }
+    }
+
+    fn get_n_outputs(&self) -> usize {
+        1
+    }
+
+    fn get_fitness_threshold(&self) -> f64 {
+        self.max_steps as f64
+    }
+
+    fn evaluate(&self, genome: &mut Genome) -> Result<f64, NeatError> {
+        Ok(self.simulate(genome)? as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poles_fall_without_control() {
+        for poles in [Poles::Single, Poles::Double] {
+            let mut state = CartPoleState::new(poles);
+            let steps = (0..10_000).take_while(|_| {
+                state.step(0.0);
+                !state.is_failed()
+            });
+            assert!(steps.count() < 1000);
+            assert!(state.angles[0] > 0.0);
+        }
+    }
+
+    #[test]
+    fn single_pole_balanced_by_controller() {
+        let mut state = CartPoleState::new(Poles::Single);
+        for _ in 0..10_000 {
+            let force = 100.0 * state.angles[0]
+                + 20.0 * state.angular_velocities[0]
+                + 1.0 * state.x
+                + 2.0 * state.x_velocity;
+            state.step(force);
+            assert!(!state.is_failed());
+        }
+        assert!(state.angles[0].abs() < 0.01);
+    }
+
+    #[test]
+    fn inputs() {
+        let genome = |task: &CartPole| Genome::new(0, task.get_n_inputs() as u32, 1);
+        for (poles, markovian, inputs) in [
+            (Poles::Single, true, 4),
+            (Poles::Single, false, 2),
+            (Poles::Double, true, 6),
+            (Poles::Double, false, 3),
+        ] {
+            let task = CartPole::new(poles, markovian, 1000);
+            assert_eq!(task.get_n_inputs(), inputs);
+            assert_eq!(
+                CartPoleState::new(poles).get_observation(markovian).len(),
+                inputs
+            );
+            // the weights are 0, so the force is 0 and the poles fall
+            let steps = task.simulate(&mut genome(&task)).unwrap();
+            assert!(steps > 0 && steps < 1000);
+        }
+    }
+}
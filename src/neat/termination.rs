@@ -0,0 +1,53 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Criteria used to stop a run. The run stops as soon as one of them is met.
+#[derive(Clone, Default)]
+pub struct Termination {
+    /// Maximum number of generations to run, at least 1
+    pub max_generations: Option<u32>,
+    /// Stop once a genome reaches this fitness
+    pub fitness_threshold: Option<f64>,
+    /// Maximum duration of the run, checked after each generation
+    pub time_limit: Option<Duration>,
+    /// Stop after this many generations without improvement of the best fitness
+    pub stagnation_limit: Option<u32>,
+}
+
+impl Termination {
+    /// Returns true if no condition is set, the run would never stop
+    pub fn is_empty(&self) -> bool {
+        self.max_generations.is_none()
+            && self.fitness_threshold.is_none()
+            && self.time_limit.is_none()
+            && self.stagnation_limit.is_none()
+    }
+}
+
+/// Reason why a run stopped
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StopReason {
+    /// The maximum number of generations was reached
+    MaxGenerations,
+    /// The best fitness reached the fitness threshold
+    FitnessThreshold,
+    /// The time limit was exceeded
+    TimeLimit,
+    /// The best fitness didn't improve for the stagnation limit
+    Stagnation,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match &self {
+                Self::MaxGenerations => "max_generations",
+                Self::FitnessThreshold => "fitness_threshold",
+                Self::TimeLimit => "time_limit",
+                Self::Stagnation => "stagnation",
+            }
+        )
+    }
+}
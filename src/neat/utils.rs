@@ -1,32 +1,109 @@
+use super::Activation;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::sync::{PoisonError, RwLock};
+
+/// Parameters of the evolution, shared by every run
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Settings {
+    /// Steepness of the sigmoid activation
     pub sigmoid_steepness: f64,
+    /// Genomes with at most this many genes aren't normalized by their size when compared
     pub small_genome_size: u32,
+    /// Weight of the excess genes in the compatibility distance
     pub similarity_c1: f64,
+    /// Weight of the disjoint genes in the compatibility distance
     pub similarity_c2: f64,
+    /// Weight of the mean weight difference of the matching genes in the compatibility distance
     pub similarity_c3: f64,
+    /// Initial max difference between two genomes of the same species
     pub max_difference: f64,
+    /// Number of species the max difference is adjusted to get
+    pub target_species: usize,
+    /// Change of the max difference when there are too many or too few species
+    pub max_difference_step: f64,
+    /// Lowest value of the max difference
+    pub max_difference_min: f64,
     /// chance of a weight being reassigned during a mutation
     pub w_mut_reassign: f64,
     /// Max value of reassigned weights
     pub w_mut_reassign_max: f64,
     /// Max uniform perturbation when mutating weights
     pub w_mut_change_max: f64,
+    /// chance of an offspring's weights being mutated
+    pub w_mut_chance: f64,
+    /// chance of an offspring getting a new link
+    pub add_link_chance: f64,
+    /// chance of an offspring getting a new node
+    pub add_node_chance: f64,
+    /// chance of an offspring being produced by crossover instead of cloning
+    pub crossover_chance: f64,
+    /// Part of each species (the best ones) allowed to reproduce
+    pub survival_threshold: f64,
+    /// Species with more individuals than this keep their champion unchanged
+    pub elitism_min_species_size: usize,
+    /// Species are reported as stagnant after this many generations without improvement, 0 disables it
+    pub species_stagnation_limit: u32,
+    /// chance of an offspring getting a random activation on one of its nodes, new runs start with it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub activation_mut_chance: f64,
+    /// Activation of the nodes added by mutations, new runs start with it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hidden_activation: Activation,
+    /// Activation of the output nodes of new genomes, new runs start with it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub output_activation: Activation,
 }
 
-pub static mut SETTINGS: Settings = Settings {
+const DEFAULT_SETTINGS: Settings = Settings {
     sigmoid_steepness: 4.9,
     small_genome_size: 19,
     similarity_c1: 1.0,
     similarity_c2: 1.0,
     similarity_c3: 0.4,
     max_difference: 3.0,
+    target_species: 10,
+    max_difference_step: 0.3,
+    max_difference_min: 0.3,
     w_mut_reassign: 0.1,
     w_mut_reassign_max: 1.0,
     w_mut_change_max: 0.3,
+    w_mut_chance: 0.8,
+    add_link_chance: 0.05,
+    add_node_chance: 0.03,
+    crossover_chance: 0.75,
+    survival_threshold: 0.2,
+    elitism_min_species_size: 5,
+    species_stagnation_limit: 15,
+    activation_mut_chance: 0.0,
+    hidden_activation: Activation::Sigmoid,
+    output_activation: Activation::Sigmoid,
 };
 
-pub fn sigmoid(x: f64) -> f64 {
-    unsafe {
-        1.0 / (1.0 + f64::exp(-SETTINGS.sigmoid_steepness * x))
+impl Default for Settings {
+    fn default() -> Self {
+        DEFAULT_SETTINGS
     }
 }
+
+/// Settings shared by every run
+static SETTINGS: RwLock<Settings> = RwLock::new(DEFAULT_SETTINGS);
+
+/// Returns a copy of the settings shared by every run
+pub fn get_settings() -> Settings {
+    *SETTINGS.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/**
+Replaces the settings shared by every run.
+The runs in progress read them as they go, changing them in the middle of a generation affects the rest of it.
+*/
+pub fn set_settings(settings: Settings) {
+    *SETTINGS.write().unwrap_or_else(PoisonError::into_inner) = settings;
+}
+
+/// Sigmoid of `x`, using the steepness of the settings
+pub fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + f64::exp(-get_settings().sigmoid_steepness * x))
+}
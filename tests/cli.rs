@@ -0,0 +1,103 @@
+//! Runs the `rust-neat` binary on small experiments, checking the files it writes and what it prints.
+
+use rust_neat::{binary, Genome};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn rust_neat(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rust-neat"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn get_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rust_neat_cli_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn run_resume_and_inspect() {
+    let dir = get_dir("run");
+    let out = dir.to_str().unwrap();
+    let output = rust_neat(&[
+        "run",
+        "--task",
+        "xor",
+        "--generations",
+        "3",
+        "--population",
+        "50",
+        "--seed",
+        "1",
+        "--out",
+        out,
+        "--quiet",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Stopped (max_generations) after 3 generations"));
+    for file in [
+        "experiment.toml",
+        "checkpoint.json",
+        "statistics.csv",
+        "best.json",
+    ] {
+        assert!(dir.join(file).exists(), "{} wasn't written", file);
+    }
+    assert!(std::fs::read_to_string(dir.join("experiment.toml"))
+        .unwrap()
+        .contains("seed = 1"));
+    assert_eq!(
+        std::fs::read_to_string(dir.join("statistics.csv"))
+            .unwrap()
+            .lines()
+            .count(),
+        4
+    );
+
+    let checkpoint = dir.join("checkpoint.json");
+    let output = rust_neat(&[
+        "resume",
+        checkpoint.to_str().unwrap(),
+        "--generations",
+        "5",
+        "--quiet",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("after 5 generations"));
+
+    let best = Genome::from_json(&std::fs::read_to_string(dir.join("best.json")).unwrap()).unwrap();
+    let output = rust_neat(&["inspect", dir.join("best.json").to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with(&format!("Genome {}\nFitness: {}\n", best.id, best.fitness)));
+    assert!(stdout.contains("Inputs: 2, outputs: 1"));
+    assert!(stdout.contains(&format!("Genes: {} (", best.genes.len())));
+
+    // the binary format gives the same summary
+    let path = dir.join("best.neat");
+    std::fs::write(&path, binary::write_genomes(&[best])).unwrap();
+    let binary_output = rust_neat(&["inspect", path.to_str().unwrap()]);
+    assert!(binary_output.status.success(), "{:?}", binary_output);
+    assert_eq!(binary_output.stdout, stdout.into_bytes());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn errors() {
+    let dir = get_dir("errors");
+    let output = rust_neat(&["run", "--task", "xorr", "--out", dir.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("error: "));
+    assert!(!dir.exists());
+
+    let output = rust_neat(&["inspect", dir.join("missing.json").to_str().unwrap()]);
+    assert!(!output.status.success());
+}
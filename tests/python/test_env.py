@@ -0,0 +1,154 @@
+"""`evaluate_env` on mock environments, following the Gymnasium API"""
+
+import json
+import unittest
+
+import rust_neat
+
+
+class Discrete:
+    def __init__(self, n, start=0):
+        self.n = n
+        self.start = start
+
+
+class Box:
+    def __init__(self, low, high):
+        self.low = low
+        self.high = high
+        self.shape = (len(low),)
+        self.dtype = float
+
+
+class MultiBinary:
+    def __init__(self, n):
+        self.n = n
+
+
+class CountEnv:
+    """Reward of 1 per step, the episode is truncated after 5 steps and terminated by the discrete action 1"""
+
+    def __init__(self, action_space):
+        self.action_space = action_space
+        self.actions = []
+        self.closed = False
+
+    def reset(self, seed=None):
+        self.steps = 0
+        return [0.5, 1.0], {}
+
+    def step(self, action):
+        self.actions.append(action)
+        self.steps += 1
+        terminated = isinstance(self.action_space, Discrete) and action == self.action_space.start + 1
+        return [0.5, 1.0], 1.0, terminated, self.steps >= 5, {}
+
+    def close(self):
+        self.closed = True
+
+
+def get_genome(weights, activation=None):
+    """Genome with 2 inputs and 2 outputs, `weights[(from, to)]` being the weights of its genes"""
+    genome = json.loads(rust_neat.Genome(2, 2).to_json())
+    for gene in genome["genes"]:
+        gene["weight"] = weights.get((gene["from"], gene["to"]), 0.0)
+    genome = rust_neat.Genome.from_json(json.dumps(genome))
+    if activation is not None:
+        for node in [3, 4]:
+            genome.set_activation(node, activation)
+    return genome
+
+
+class TestEvaluateEnv(unittest.TestCase):
+    def evaluate(self, genome, space, episodes=1, max_steps=100, **transforms):
+        self.envs = []
+
+        def make_env():
+            self.envs.append(CountEnv(space))
+            return self.envs[-1]
+
+        return rust_neat.evaluate_env(genome, make_env, episodes, max_steps, **transforms)
+
+    def test_discrete(self):
+        # the first output is the largest, the episodes are truncated, all in the same environment
+        genome = get_genome({(2, 3): 1.0})
+        self.assertEqual(self.evaluate(genome, Discrete(2), episodes=3), 5.0)
+        self.assertEqual(len(self.envs), 1)
+        self.assertEqual(self.envs[-1].actions, [0] * 15)
+        self.assertTrue(self.envs[-1].closed)
+
+        genome = get_genome({(2, 4): 1.0})
+        self.assertEqual(self.evaluate(genome, Discrete(2)), 1.0)
+        self.assertEqual(self.envs[-1].actions, [1])
+        self.assertEqual(self.evaluate(genome, Discrete(2, start=3)), 1.0)
+        self.assertEqual(self.envs[-1].actions, [4])
+
+    def test_box(self):
+        # the sigmoid of 0 is 0.5, the middle of the bounds
+        self.assertEqual(self.evaluate(get_genome({}), Box([-2.0, 0.0], [2.0, 10.0]), max_steps=3), 3.0)
+        self.assertEqual([list(action) for action in self.envs[-1].actions], [[0.0, 5.0]] * 3)
+
+        # the outputs outside of [0, 1] are clamped to the bounds
+        genome = get_genome({(2, 3): 2.0, (2, 4): -1.0}, activation="identity")
+        self.evaluate(genome, Box([-2.0, 0.0], [2.0, 10.0]), max_steps=1)
+        self.assertEqual(list(self.envs[-1].actions[0]), [2.0, 0.0])
+        genome = get_genome({(2, 3): 0.25, (2, 4): 0.5}, activation="identity")
+        self.evaluate(genome, Box([-2.0, 0.0], [2.0, 10.0]), max_steps=1)
+        self.assertEqual(list(self.envs[-1].actions[0]), [-1.0, 5.0])
+
+    def test_unsupported_spaces(self):
+        for space, message in [
+            (MultiBinary(2), "MultiBinary"),
+            (object(), "object"),
+            (Box([-1.0], [1.0]), "dimensions"),
+            (Box([float("-inf")] * 2, [1.0] * 2), "unbounded"),
+        ]:
+            with self.assertRaisesRegex(ValueError, message):
+                self.evaluate(get_genome({}), space)
+            self.assertTrue(self.envs[-1].closed)
+
+    def test_transforms(self):
+        observations = []
+        outputs = []
+
+        def obs_transform(observation):
+            observations.append(observation)
+            return observation[::-1]
+
+        def action_transform(output):
+            outputs.append(output)
+            return [round(value) for value in output]
+
+        # the observation is reversed, the inputs are [1.0, 0.5]
+        genome = get_genome({(1, 3): 1.0}, activation="identity")
+        reward = self.evaluate(
+            genome,
+            MultiBinary(2),
+            max_steps=2,
+            obs_transform=obs_transform,
+            action_transform=action_transform,
+        )
+        self.assertEqual(reward, 2.0)
+        self.assertEqual(observations, [[0.5, 1.0]] * 2)
+        self.assertEqual(outputs, [[1.0, 0.0]] * 2)
+        self.assertEqual(self.envs[-1].actions, [[1, 0]] * 2)
+
+        with self.assertRaises(ZeroDivisionError):
+            self.evaluate(genome, Discrete(2), obs_transform=lambda observation: 1 / 0)
+        self.assertTrue(self.envs[-1].closed)
+
+    def test_invalid_arguments(self):
+        with self.assertRaises(ValueError):
+            self.evaluate(get_genome({}), Discrete(2), episodes=0)
+
+    def test_fitness_function(self):
+        neat = rust_neat.NEAT(20, 2, 2, seed=1)
+        best, _ = neat.run(
+            lambda genome: rust_neat.evaluate_env(genome, lambda: CountEnv(Discrete(2)), 1, 10),
+            max_generations=2,
+        )
+        self.assertIn(best.fitness, [1.0, 5.0])
+
+
+if __name__ == "__main__":
+    unittest.main()
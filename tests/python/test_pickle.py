@@ -0,0 +1,55 @@
+"""Pickling of genomes and runs, run with `python -m unittest discover tests/python` after `maturin develop`"""
+
+import copy
+import pickle
+import unittest
+
+import rust_neat
+
+
+def fitness(genome):
+    return genome.compute([1.0, 0.0])[0]
+
+
+class TestPickle(unittest.TestCase):
+    def test_genome_round_trip(self):
+        neat = rust_neat.NEAT(20, 2, 1, seed=1)
+        for _ in range(3):
+            neat.run_one_gen(fitness)
+        genome = neat.best_genome
+
+        for restored in [pickle.loads(pickle.dumps(genome)), copy.deepcopy(genome)]:
+            self.assertEqual(restored.id, genome.id)
+            self.assertEqual(restored.fitness, genome.fitness)
+            self.assertEqual(restored.adj_fitness, genome.adj_fitness)
+            self.assertEqual(restored.to_json(), genome.to_json())
+            self.assertEqual(restored.compute([0.3, -0.7]), genome.compute([0.3, -0.7]))
+
+    def test_run_round_trip(self):
+        neat = rust_neat.NEAT(20, 2, 1, seed=1)
+        for _ in range(3):
+            neat.run_one_gen(fitness)
+
+        restored = pickle.loads(pickle.dumps(neat))
+        self.assertEqual(restored.generation, 3)
+        self.assertEqual(len(restored.statistics), 3)
+        self.assertEqual(
+            [genome.id for genome in restored.population],
+            [genome.id for genome in neat.population],
+        )
+
+        # the random generator is restored too, the runs go on identically
+        neat.run_one_gen(fitness)
+        restored.run_one_gen(fitness)
+        self.assertEqual(
+            [(genome.id, genome.fitness) for genome in restored.population],
+            [(genome.id, genome.fitness) for genome in neat.population],
+        )
+
+    def test_invalid_state(self):
+        with self.assertRaises(ValueError):
+            rust_neat.NEAT._from_state("{}")
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,57 @@
+//! The built-in tasks must stay solvable: these runs are seeded, so they always take the same number
+//! of generations, and a change of the algorithm making them fail has to be looked at.
+
+use rust_neat::tasks::{CartPole, Poles, Task, Xor};
+use rust_neat::{StopReason, Termination, NEAT};
+
+#[test]
+fn xor_is_solved() {
+    let task = Xor;
+    let mut neat = NEAT::new(150, task.get_n_inputs(), task.get_n_outputs(), Some(1)).unwrap();
+    let termination = Termination {
+        max_generations: Some(200),
+        fitness_threshold: Some(task.get_fitness_threshold()),
+        ..Termination::default()
+    };
+    let (mut best, reason) = neat
+        .run_with(|genome| task.evaluate(genome), &termination)
+        .unwrap();
+    assert_eq!(reason, StopReason::FitnessThreshold);
+    assert!(neat.get_generation() <= 200);
+
+    for (a, b) in [(0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0)] {
+        let output = best.compute(vec![a, b]).unwrap()[0];
+        assert_eq!(output.round(), if a != b { 1.0 } else { 0.0 });
+    }
+}
+
+#[test]
+fn xor_run_is_reproducible() {
+    let run = || {
+        let mut neat = NEAT::new(100, 2, 1, Some(7)).unwrap();
+        let termination = Termination {
+            max_generations: Some(20),
+            ..Termination::default()
+        };
+        let (best, _) = neat
+            .run_with(|genome| Xor.evaluate(genome), &termination)
+            .unwrap();
+        (best.id, best.fitness)
+    };
+    assert_eq!(run(), run());
+}
+
+#[test]
+fn double_pole_is_balanced() {
+    let task = CartPole::new(Poles::Double, true, 10_000);
+    let mut neat = NEAT::new(150, task.get_n_inputs(), task.get_n_outputs(), Some(3)).unwrap();
+    let termination = Termination {
+        max_generations: Some(50),
+        fitness_threshold: Some(task.get_fitness_threshold()),
+        ..Termination::default()
+    };
+    let (_, reason) = neat
+        .run_with(|genome| task.evaluate(genome), &termination)
+        .unwrap();
+    assert_eq!(reason, StopReason::FitnessThreshold);
+}